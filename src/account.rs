@@ -1,7 +1,11 @@
 use std::future::Pending;
 
+use near_sdk::ext_contract;
+
 use crate::{*, utils::{get_access_limit, verify_secp256k1, get}};
+use crate::drip::{get_decay_config, CONTENT_ACTION};
 
+// keys of the legacy string map, only read when migrating an `OldAccount`
 const ACCOUNT_ID: &str = "account_id";
 const REGISTERED: &str = "registered";
 const DRIP: &str = "drip";
@@ -9,12 +13,12 @@ const ONE_DAY_TIMESTAMP: &str = "one_day_timestamp";
 const CONTENT_COUNT: &str = "content_count";
 const TOTAL_CONTENT_COUNT: &str = "total_content_count";
 const PERMANENT: &str = "permanent";
-const EVENT: &str = "event";
+const SIGNATURE_SUFFIX: &str = "_signature";
 
 #[derive(BorshDeserialize, BorshSerialize)]
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd)]
 pub enum AssetKey {
     FT(AccountId),
     NFT(AccountId, Option<String>),               //nft token id, token id
@@ -22,19 +26,113 @@ pub enum AssetKey {
 }
 
 
-fn get_account_decay(count: u64) -> u32 {
-    if count <= 10 {
-        return 100
-    } 
-    40
+#[ext_contract(ext_nft_core)]
+pub trait NonFungibleTokenCore {
+    fn nft_transfer(&mut self, receiver_id: AccountId, token_id: String, approval_id: Option<u64>, memo: Option<String>);
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Clone)]
 #[derive(Debug)]
 pub struct Account {
-    pub data: HashMap<String, String>,
-    // one_day_timestamp: u64,   //update after 24h
-    // content_count: u64
+    pub account_id: AccountId,
+    pub registered: bool,
+    pub permanent: bool,
+    pub drip: u128,
+    pub activity: Activity,
+    pub balances: HashMap<AssetKey, u128>,
+    pub signatures: HashMap<String, (String, U64)>,     //public key -> (signature, timestamp)
+    pub conditions: HashMap<CryptoHash, U64>,           //condition hash -> timestamp
+    pub app_data: HashMap<AccountId, HashMap<String, String>>   //written by `Instruction::Write`, keyed by writer
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+#[derive(Debug)]
+pub struct Activity {
+    pub window_timestamp: u64,   //reset after the decay window passed
+    pub action_counts: HashMap<String, u64>,    //actions inside current window
+    pub total_content_count: u64
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub enum VAccount {
+    Current(Account)
+}
+
+impl From<VAccount> for Account {
+    fn from(v: VAccount) -> Self {
+        match v {
+            VAccount::Current(account) => account
+        }
+    }
+}
+
+impl From<Account> for VAccount {
+    fn from(account: Account) -> Self {
+        VAccount::Current(account)
+    }
+}
+
+/// Layout of accounts before they were typed, still stored under `StorageKey::Account`.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[derive(Debug)]
+pub struct OldAccount {
+    pub data: HashMap<String, String>
+}
+
+impl OldAccount {
+    fn get_data<T>(&self, key: &str) -> Option<T> 
+    where T: for<'a> Deserialize<'a>
+    {
+        serde_json::from_str::<T>(self.data.get(key)?).ok()
+    }
+
+    fn get_number(&self, key: &str) -> u64 {
+        self.data.get(key).and_then(|v| v.parse().ok()).unwrap_or(0)
+    }
+
+    pub fn migrate(self, account_id: &AccountId) -> Account {
+        let mut account = Account::new(account_id);
+        account.registered = self.get_data::<bool>(REGISTERED).unwrap_or(false);
+        account.permanent = self.get_data::<bool>(PERMANENT).unwrap_or(false);
+        account.drip = self.get_data::<U128>(DRIP).unwrap_or(U128::from(0)).0;
+        account.activity = Activity {
+            window_timestamp: self.get_number(ONE_DAY_TIMESTAMP),
+            action_counts: HashMap::new(),
+            total_content_count: self.get_number(TOTAL_CONTENT_COUNT)
+        };
+        account.activity.action_counts.insert(CONTENT_ACTION.to_string(), self.get_number(CONTENT_COUNT));
+        for (key, value) in self.data.iter() {
+            match key.as_str() {
+                ACCOUNT_ID | REGISTERED | PERMANENT | DRIP | ONE_DAY_TIMESTAMP | CONTENT_COUNT | TOTAL_CONTENT_COUNT => continue,
+                _ => {}
+            }
+            if let Some(public_key) = key.strip_suffix(SIGNATURE_SUFFIX) {
+                if let Ok(signature) = serde_json::from_str::<(String, U64)>(value) {
+                    account.signatures.insert(public_key.to_string(), signature);
+                    continue
+                }
+            }
+            if let Ok(asset) = serde_json::from_str::<AssetKey>(key) {
+                let balance = serde_json::from_str::<U128>(value).unwrap_or(U128::from(0));
+                account.balances.insert(asset, balance.0);
+                continue
+            }
+            if let Ok(condition) = serde_json::from_str::<Condition>(key) {
+                if let Ok(timestamp) = serde_json::from_str::<U64>(value) {
+                    account.conditions.insert(get_condition_key(&condition), timestamp);
+                }
+                continue
+            }
+            if let (Ok(app_id), Ok(map)) = (AccountId::from_str(key), serde_json::from_str::<HashMap<String, String>>(value)) {
+                account.app_data.insert(app_id, map);
+            }
+        }
+        account
+    }
+}
+
+fn get_condition_key(condition: &Condition) -> CryptoHash {
+    env::sha256(&condition.try_to_vec().unwrap()).try_into().unwrap()
 }
 
 #[derive(BorshDeserialize, BorshSerialize)]
@@ -76,7 +174,8 @@ pub enum Condition {
     FTCondition(FTCondition),
     NFTCondition(NFTCondition),
     DripCondition(DripCondition),
-    SignCondition(SignCondition)
+    SignCondition(SignCondition),
+    CompositeCondition(CompositeCondition)
 }
 
 #[derive(BorshDeserialize, BorshSerialize)]
@@ -116,38 +215,53 @@ pub struct SignCondition {
     pub public_key: String
 }
 
-impl Account {
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[derive(Debug, Clone)]
+pub struct CompositeCondition {
+    pub relationship: Relationship,
+    pub items: Vec<Access>     //every item keeps its own expire_duration and is_payment
+}
 
-    pub fn new(account_id: &AccountId) -> Self {
-        let mut this = Self {
-            data: HashMap::new(),
-        };
-        this.data.insert(ACCOUNT_ID.to_string(), account_id.to_string());
+// borsh derive puts bounds on field types, which never resolve for a recursive condition tree
+impl BorshSerialize for CompositeCondition {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        BorshSerialize::serialize(&self.relationship, writer)?;
+        BorshSerialize::serialize(&self.items, writer)
+    }
+}
 
-        this.data.insert(REGISTERED.to_string(), json!(false).to_string());
-        this.data.insert(DRIP.to_string(), 0.to_string());
-        this.data.insert(ONE_DAY_TIMESTAMP.to_string(), env::block_timestamp().to_string());
-        this.data.insert(CONTENT_COUNT.to_string(), 0.to_string());
-        this.data.insert(TOTAL_CONTENT_COUNT.to_string(), 0.to_string());
-        
-        this
+impl BorshDeserialize for CompositeCondition {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        Ok(Self {
+            relationship: BorshDeserialize::deserialize(buf)?,
+            items: BorshDeserialize::deserialize(buf)?
+        })
     }
+}
 
-    pub fn get_data<T>(&self, key: &str) -> Option<T> 
-    where T: for<'a> Deserialize<'a>
-    {
-        let value = match self.data.get(key) {
-            Some(v) => v,
-            None => return None
-        };
-        match serde_json::from_str::<T>(value) {
-            Ok(res) => Some(res),
-            Err(_) => None
+impl Account {
+
+    pub fn new(account_id: &AccountId) -> Self {
+        Self {
+            account_id: account_id.clone(),
+            registered: false,
+            permanent: false,
+            drip: 0,
+            activity: Activity {
+                window_timestamp: env::block_timestamp(),
+                action_counts: HashMap::new(),
+                total_content_count: 0
+            },
+            balances: HashMap::new(),
+            signatures: HashMap::new(),
+            conditions: HashMap::new(),
+            app_data: HashMap::new()
         }
     }
 
     pub fn account_id(&self) -> AccountId {
-        AccountId::from_str(self.data.get(ACCOUNT_ID).unwrap()).unwrap()
+        self.account_id.clone()
     }
 
     pub fn registered(self) -> Self {
@@ -174,7 +288,7 @@ impl Account {
         let access_limit = get_access_limit();
         let mut registered = match access_limit {
             AccessLimit::Free => return true,
-            _ => self.get_data::<bool>(REGISTERED).unwrap()
+            _ => self.registered
         };
         if registered {
             if self.is_permanent() {
@@ -189,22 +303,21 @@ impl Account {
     }
 
     pub fn is_permanent(&self) -> bool {
-        self.get_data::<bool>(PERMANENT).unwrap_or(false)
+        self.permanent
     }
 
     pub fn set_registered(&mut self, registered: bool) {
-        self.data.insert(REGISTERED.to_string(), json!(registered).to_string());
+        self.registered = registered;
     }
 
     pub fn set_permanent(&mut self, is_permanent: bool) {
-        self.data.insert(PERMANENT.to_string(), json!(is_permanent).to_string());
+        self.permanent = is_permanent;
     }
 
     pub fn is_expired(&self, access: &Access) -> bool {
-        let key = json!(access.condition).to_string();
         match access.expire_duration {
             Some(expire_duration) => {
-                let timestamp = self.get_data::<U64>(&key).unwrap_or(U64::from(0));
+                let timestamp = self.conditions.get(&get_condition_key(&access.condition)).cloned().unwrap_or(U64::from(0));
                 env::block_timestamp() > timestamp.0 + expire_duration.0
             },
             None => false
@@ -212,71 +325,71 @@ impl Account {
     }
 
     pub fn set_timestamp(&mut self, access: &Access, timestamp: U64) {
-        let key = json!(access.condition).to_string();
-        self.data.insert(key, json!(timestamp).to_string());
+        self.conditions.insert(get_condition_key(&access.condition), timestamp);
     }
 
     pub fn get_drip(&self) -> u128 {
-        let drip = self.get_data::<U128>(DRIP).unwrap_or(U128::from(0));
-        drip.0
+        self.drip
     }
 
     pub fn increase_drip(&mut self, amount: u128) {
-        let drip = self.get_drip();
-
-        if let Some(new_drip) = drip.checked_add(amount) {
-            let drip: U128 = new_drip.into();
-            self.data.insert(DRIP.to_string(), json!(drip).to_string());
+        if let Some(new_drip) = self.drip.checked_add(amount) {
+            self.drip = new_drip;
         }
     }
 
     pub fn decrease_drip(&mut self, amount: u128) {
-        let mut drip = self.get_drip();
-        if let Some(new_drip) = drip.checked_sub(amount) {
-            drip = new_drip;
+        if let Some(new_drip) = self.drip.checked_sub(amount) {
+            self.drip = new_drip;
         } else {
             panic!("not enough balance");
         }
-        let drip: U128 = drip.into();
-        self.data.insert(DRIP.to_string(), json!(drip).to_string());
     }
 
-    pub fn get_account_decay(&self) -> u32 {
-        let timestamp: u64 = self.data.get(&ONE_DAY_TIMESTAMP.to_string()).unwrap_or(&env::block_timestamp().to_string()).parse().unwrap();
-        let mut content_count = 0;
-        if env::block_timestamp() - timestamp < 60 * 60 * 24 * 1000_000_000 {
-            content_count = (self.data.get(&CONTENT_COUNT.to_string()).unwrap_or(&0.to_string())).parse().unwrap();
+    pub fn get_action_count(&self, action: &str) -> u64 {
+        if env::block_timestamp() - self.activity.window_timestamp < get_decay_config().window.0 {
+            return *self.activity.action_counts.get(action).unwrap_or(&0)
         }
-        get_account_decay(content_count)
+        0
     }
 
-    pub fn increase_content_count(&mut self) {
-        let timestamp: u64 = self.data.get(&ONE_DAY_TIMESTAMP.to_string()).unwrap_or(&env::block_timestamp().to_string()).parse().unwrap();
-        if env::block_timestamp() - timestamp > 60 * 60 * 24 * 1000_000_000 {
-            self.data.insert(ONE_DAY_TIMESTAMP.to_string(), env::block_timestamp().to_string());
-            self.data.insert(CONTENT_COUNT.to_string(), 0.to_string());
+    pub fn get_account_decay(&self, action: &str) -> u32 {
+        get_decay_config().get_account_decay(action, self.get_action_count(action))
+    }
+
+    pub fn increase_action_count(&mut self, action: &str) {
+        if env::block_timestamp() - self.activity.window_timestamp > get_decay_config().window.0 {
+            self.activity.window_timestamp = env::block_timestamp();
+            self.activity.action_counts.clear();
         }
-        let content_count: u32 = (self.data.get(&CONTENT_COUNT.to_string()).unwrap_or(&0.to_string())).parse().unwrap();
-        self.data.insert(CONTENT_COUNT.to_string(), (content_count + 1).to_string());
-        let total_content_count: u32 = (self.data.get(&TOTAL_CONTENT_COUNT.to_string()).unwrap_or(&0.to_string())).parse().unwrap();
-        self.data.insert(TOTAL_CONTENT_COUNT.to_string(), (total_content_count + 1).to_string());
+        *self.activity.action_counts.entry(action.to_string()).or_insert(0) += 1;
+    }
+
+    pub fn increase_content_count(&mut self) {
+        self.increase_action_count(CONTENT_ACTION);
+        self.activity.total_content_count += 1;
+    }
+
+    pub fn get_app_data(&self, app_id: &AccountId) -> Option<HashMap<String, String>> {
+        self.app_data.get(app_id).cloned()
+    }
+
+    pub fn set_app_data(&mut self, app_id: AccountId, data: HashMap<String, String>) {
+        self.app_data.insert(app_id, data);
     }
 
 
 //////////////////////////////////////////////////////////  Deposit Part ////////////////////////////////////////////////////////////
 
     pub fn get_balance(&self, balance: &AssetKey) -> u128 {
-        let drip: U128 = serde_json::from_str(self.data.get(&json!(balance).to_string()).unwrap_or(&"0".to_string())).unwrap_or(U128::from(0));
-        drip.0
+        *self.balances.get(balance).unwrap_or(&0)
     }
 
     pub fn increase_balance(&mut self, asset: AssetKey, amount: u128) {
-        let mut balance = self.get_balance(&asset);
+        let balance = self.get_balance(&asset);
         if let Some(new_balance) = balance.checked_add(amount) {
-            balance = new_balance;
+            self.balances.insert(asset, new_balance);
         }
-        let balance: U128 = balance.into();
-        self.data.insert(json!(asset).to_string(), json!(balance).to_string());
     }
 
     pub fn decrease_balance(&mut self, asset: AssetKey, amount: u128) {
@@ -285,24 +398,43 @@ impl Account {
                 return
             }
         }
-        let mut balance = self.get_balance(&asset);
+        let balance = self.get_balance(&asset);
         if let Some(new_balance) = balance.checked_sub(amount) {
-            balance = new_balance;
+            self.balances.insert(asset, new_balance);
         } else {
             panic!("not enough balance");
         }
-        let balance: U128 = balance.into();
-        self.data.insert(json!(asset).to_string(), json!(balance).to_string());
+    }
+
+    pub fn increase_nft(&mut self, contract_id: AccountId, token_id: String) {
+        let asset = AssetKey::NFT(contract_id.clone(), Some(token_id));
+        assert!(self.get_balance(&asset) == 0, "token already deposited");
+        self.increase_balance(asset, 1);
+        self.increase_balance(AssetKey::NFT(contract_id, None), 1);
+    }
+
+    /// Gives back an asset taken out for a transfer that failed, keeping nft token balances consistent.
+    pub fn refund_asset(&mut self, asset: AssetKey, amount: u128) {
+        match asset {
+            AssetKey::NFT(contract_id, Some(token_id)) => self.increase_nft(contract_id, token_id),
+            _ => self.increase_balance(asset, amount)
+        }
+    }
+
+    pub fn decrease_nft(&mut self, contract_id: AccountId, token_id: String) {
+        let asset = AssetKey::NFT(contract_id.clone(), Some(token_id));
+        assert!(self.get_balance(&asset) == 1, "token not found");
+        self.balances.remove(&asset);
+        self.decrease_balance(AssetKey::NFT(contract_id, None), 1);
     }
 ////////////////////////////////////////////////////////  Condition Part ////////////////////////////////////////////////////////////////
 
     pub fn get_signature(&self, public_key: String) -> Option<(String, U64)> {
-        let key = public_key + "_signature";
-        self.get_data::<(String, U64)>(&key)
+        self.signatures.get(&public_key).cloned()
     }
 
     pub fn set_signature(&mut self, public_key: String, signature: String, timestamp: U64) {
-        self.data.insert(public_key + "_signature", json!((signature, timestamp)).to_string());
+        self.signatures.insert(public_key, (signature, timestamp));
     } 
 
     pub fn check_condition(&self, access: &Access) -> bool {
@@ -313,7 +445,9 @@ impl Account {
             Condition::FTCondition(ft) => {
                 self.get_balance(&AssetKey::FT(ft.token_id.clone())) >= ft.amount_to_access.0 && !access.is_payment
             }
-            Condition::NFTCondition(_) => todo!(),
+            Condition::NFTCondition(nft) => {
+                self.get_balance(&AssetKey::NFT(nft.token_id.clone(), None)) >= nft.amount_to_access.0 && !access.is_payment
+            },
             Condition::DripCondition(drip) => {
                 self.get_balance(&AssetKey::Drip((drip.token_id.clone(), drip.contract_id.clone()))) >= drip.amount_to_access.0 && !access.is_payment
             },
@@ -334,6 +468,12 @@ impl Account {
                     },
                     None => false
                 }
+            },
+            Condition::CompositeCondition(composite) => {
+                match composite.relationship {
+                    Relationship::And => composite.items.iter().all(|item| self.check_condition(item)),
+                    Relationship::Or => composite.items.iter().any(|item| self.check_condition(item))
+                }
             }
         }
     }
@@ -356,7 +496,31 @@ impl Account {
                     false
                 }
             }
-            Condition::NFTCondition(_) => todo!(),
+            Condition::NFTCondition(nft) => {
+                if self.get_balance(&AssetKey::NFT(nft.token_id.clone(), None)) < nft.amount_to_access.0 {
+                    return false
+                }
+                if access.is_payment {
+                    // paid nfts are moved into the community's custody, caller picks which ones
+                    let token_ids: Vec<String> = match options.as_ref().and_then(|options| options.get("nft_token_ids")) {
+                        Some(v) => serde_json::from_str(v).unwrap_or_default(),
+                        None => return false
+                    };
+                    if (token_ids.len() as u128) < nft.amount_to_access.0 {
+                        return false
+                    }
+                    let mut community = get_account(&env::current_account_id());
+                    for token_id in token_ids.into_iter().take(nft.amount_to_access.0 as usize) {
+                        self.decrease_nft(nft.token_id.clone(), token_id.clone());
+                        community.increase_nft(nft.token_id.clone(), token_id);
+                    }
+                    set_account(&env::current_account_id(), &community);
+                }
+                if access.expire_duration.is_some() {
+                    self.set_timestamp(access, U64::from(env::block_timestamp()));
+                }
+                true
+            },
             Condition::DripCondition(drip) => {
                 if self.get_balance(&AssetKey::Drip((drip.token_id.clone(), drip.contract_id.clone()))) >= drip.amount_to_access.0 {
                     if access.is_payment && drip.token_id.is_some() {
@@ -386,6 +550,33 @@ impl Account {
                 }
                 self.set_signature(sign_condition.public_key.clone(), sign.clone(), timestamp);
                 true
+            },
+            Condition::CompositeCondition(composite) => {
+                // work on a copy so a failed branch does not leave half paid conditions behind
+                let mut passed = false;
+                match composite.relationship {
+                    Relationship::And => {
+                        let mut account = self.clone();
+                        if composite.items.iter().all(|item| account.set_condition(item, options.clone())) {
+                            *self = account;
+                            passed = true;
+                        }
+                    },
+                    Relationship::Or => {
+                        for item in composite.items.iter() {
+                            let mut account = self.clone();
+                            if account.set_condition(item, options.clone()) {
+                                *self = account;
+                                passed = true;
+                                break
+                            }
+                        }
+                    }
+                }
+                if passed && access.expire_duration.is_some() {
+                    self.set_timestamp(access, U64::from(env::block_timestamp()));
+                }
+                passed
             }
         }
     } 
@@ -395,10 +586,12 @@ impl Account {
 
 mod test {
     use std::collections::HashMap;
+    use std::str::FromStr;
 
-    use near_sdk::{json_types::U64, serde_json::{json, self}};
+    use near_sdk::{AccountId, json_types::{U64, U128}, serde_json::{json, self}};
 
     use crate::utils::{verify_secp256k1, verify};
+    use super::{Access, Account, AssetKey, Condition, CompositeCondition, FTCondition, NFTCondition, OldAccount, Relationship};
 
 
     #[test]
@@ -418,4 +611,93 @@ mod test {
         let mut pass = verify_secp256k1(message.as_bytes().to_vec(), sign.to_string(), public_key);
         println!("{:?}", pass);
     }
+
+    #[test]
+    pub fn test_nft_condition() {
+        let token_id = AccountId::from_str("nft.testnet").unwrap();
+        let access = Access {
+            condition: Condition::NFTCondition(NFTCondition {
+                token_id: token_id.clone(),
+                amount_to_access: U128::from(2)
+            }),
+            expire_duration: None,
+            is_payment: false,
+            options: None
+        };
+        let mut account = Account::new(&AccountId::from_str("bhc13.testnet").unwrap());
+        account.increase_nft(token_id.clone(), "1".to_string());
+        assert!(!account.set_condition(&access, None));
+        account.increase_nft(token_id.clone(), "2".to_string());
+        assert!(account.set_condition(&access, None));
+        account.decrease_nft(token_id.clone(), "1".to_string());
+        assert_eq!(account.get_balance(&AssetKey::NFT(token_id, None)), 1);
+    }
+
+    #[test]
+    pub fn test_migrate_old_account() {
+        let account_id = AccountId::from_str("bhc13.testnet").unwrap();
+        let condition = Condition::FTCondition(FTCondition {
+            token_id: AccountId::from_str("ft.testnet").unwrap(),
+            amount_to_access: U128::from(100)
+        });
+        let mut data = HashMap::new();
+        data.insert("account_id".to_string(), account_id.to_string());
+        data.insert("registered".to_string(), json!(true).to_string());
+        data.insert("drip".to_string(), json!(U128::from(300)).to_string());
+        data.insert("content_count".to_string(), "13".to_string());
+        data.insert("total_content_count".to_string(), "20".to_string());
+        data.insert(json!(AssetKey::FT(AccountId::from_str("near").unwrap())).to_string(), json!(U128::from(50)).to_string());
+        data.insert(json!(condition).to_string(), json!(U64::from(7)).to_string());
+        data.insert("abc_signature".to_string(), json!(("sign".to_string(), U64::from(8))).to_string());
+        data.insert("app.testnet".to_string(), json!({"key": "val"}).to_string());
+
+        let account = OldAccount { data }.migrate(&account_id);
+        assert!(account.registered && !account.permanent);
+        assert_eq!(account.drip, 300);
+        assert_eq!(account.get_action_count("content"), 13);
+        assert_eq!(account.activity.total_content_count, 20);
+        assert_eq!(account.get_balance(&AssetKey::FT(AccountId::from_str("near").unwrap())), 50);
+        assert_eq!(account.get_signature("abc".to_string()), Some(("sign".to_string(), U64::from(8))));
+        assert_eq!(account.get_app_data(&AccountId::from_str("app.testnet").unwrap()).unwrap().get("key").unwrap(), "val");
+        assert_eq!(account.conditions.len(), 1);
+    }
+
+    #[test]
+    pub fn test_composite_condition() {
+        let ft_id = AccountId::from_str("ft.testnet").unwrap();
+        let nft_id = AccountId::from_str("nft.testnet").unwrap();
+        let ft_access = Access {
+            condition: Condition::FTCondition(FTCondition {
+                token_id: ft_id.clone(),
+                amount_to_access: U128::from(100)
+            }),
+            expire_duration: None,
+            is_payment: false,
+            options: None
+        };
+        let nft_access = Access {
+            condition: Condition::NFTCondition(NFTCondition {
+                token_id: nft_id.clone(),
+                amount_to_access: U128::from(1)
+            }),
+            expire_duration: None,
+            is_payment: false,
+            options: None
+        };
+        let composite = |relationship| Access {
+            condition: Condition::CompositeCondition(CompositeCondition {
+                relationship,
+                items: vec![ft_access.clone(), nft_access.clone()]
+            }),
+            expire_duration: None,
+            is_payment: false,
+            options: None
+        };
+        let mut account = Account::new(&AccountId::from_str("bhc13.testnet").unwrap());
+        account.increase_balance(AssetKey::FT(ft_id), 100);
+        assert!(account.set_condition(&composite(Relationship::Or), None));
+        assert!(!account.set_condition(&composite(Relationship::And), None));
+        account.increase_nft(nft_id, "1".to_string());
+        assert!(account.set_condition(&composite(Relationship::And), None));
+    }
 }
\ No newline at end of file
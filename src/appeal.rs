@@ -0,0 +1,319 @@
+use crate::*;
+use post::{Hierarchy, ContentState};
+use account::AssetKey;
+use utils::{get, check, get_content_hash};
+
+/// Time the author has to appeal a removal, 7 days in nanoseconds.
+pub const APPEAL_WINDOW: u64 = 7 * 24 * 3600 * 1_000_000_000;
+
+#[derive(BorshDeserialize, BorshSerialize)]
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[derive(Debug, Clone)]
+pub struct Appeal {
+    pub asset: AssetKey,
+    pub amount: U128,
+    pub timestamp: U64
+}
+
+/// What a moderator removed, kept so the author can appeal and the content marker can be put back.
+/// Removals not appealed by `appeal_until` can be swept.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[derive(Debug, Clone)]
+pub struct Removal {
+    pub hierarchies: Vec<Hierarchy>,
+    pub moderator: AccountId,
+    pub mod_level: u32,
+    pub reporters: Vec<AccountId>,
+    pub report_drips: Vec<(AccountId, String, U128)>,
+    pub reply_count: u8,
+    pub state: ContentState,
+    pub timestamp: U64,
+    pub appeal_until: U64,
+    pub appeal: Option<Appeal>
+}
+
+fn get_removals() -> LookupMap<String, Removal> {
+    LookupMap::new(StorageKey::Removals)
+}
+
+pub fn get_removal(hierarchy_hash: &String) -> Option<Removal> {
+    get_removals().get(hierarchy_hash)
+}
+
+fn get_marker_key(hierarchy_hash: &str) -> Vec<u8> {
+    CryptoHash::from(Base58CryptoHash::try_from(hierarchy_hash.to_string()).unwrap()).to_vec()
+}
+
+/// Called before a moderator removes the content marker and its state.
+pub(crate) fn record_removal(hierarchy_hash: &String, hierarchies: Vec<Hierarchy>, moderator: AccountId, mod_level: u32, reporters: Vec<AccountId>, report_drips: Vec<(AccountId, String, U128)>) {
+    get_removals().insert(hierarchy_hash, &Removal {
+        hierarchies,
+        moderator,
+        mod_level,
+        reporters,
+        report_drips,
+        reply_count: get::<u8>(&get_marker_key(hierarchy_hash)).unwrap_or(0),
+        state: post::get_content_state(hierarchy_hash),
+        timestamp: env::block_timestamp().into(),
+        appeal_until: (env::block_timestamp() + APPEAL_WINDOW).into(),
+        appeal: None
+    });
+}
+
+/// Splits a forfeited stake between the reporters, what can't be paid out stays with the community.
+fn share_stake(asset: &AssetKey, amount: u128, reporters: &[AccountId]) -> Vec<(AccountId, U128)> {
+    let mut payouts = Vec::new();
+    let mut rest = amount;
+    if !reporters.is_empty() {
+        let share = amount / reporters.len() as u128;
+        for account_id in reporters {
+            let mut account = match get_account(account_id).get_registered() {
+                Some(account) => account,
+                None => continue
+            };
+            account.increase_balance(asset.clone(), share);
+            set_account(account_id, &account);
+            payouts.push((account_id.clone(), share.into()));
+            rest -= share;
+        }
+    }
+    let mut community = get_account(&env::current_account_id()).registered();
+    community.increase_balance(asset.clone(), rest);
+    set_account(&env::current_account_id(), &community);
+    payouts
+}
+
+#[near_bindgen]
+impl Community {
+    /// Author of removed content stakes `amount` of a deposited FT or drip token to have it reviewed
+    /// by a moderator above the one who removed it.
+    pub fn appeal(&mut self, hierarchies: Vec<Hierarchy>, asset: AssetKey, amount: U128) {
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = get_predecessor_id();
+        assert!(hierarchies.last().map(|v| v.account_id == sender_id).unwrap_or(false), "not content owner");
+        match &asset {
+            AssetKey::FT(_) | AssetKey::Drip((Some(_), _)) => {},
+            _ => panic!("stake must be ft or drip")
+        }
+        assert!(amount.0 > 0, "stake required");
+
+        let hierarchy_hash = get_content_hash(hierarchies.clone(), None, true).unwrap();
+        let mut removal = get_removal(&hierarchy_hash).expect("removal not found");
+        assert!(removal.appeal.is_none(), "already appealed");
+        assert!(env::block_timestamp() <= removal.appeal_until.0, "appeal window closed");
+        assert!(removal.mod_level < u32::MAX, "can not appeal");
+
+        let mut account = get_account(&sender_id).registered();
+        account.decrease_balance(asset.clone(), amount.0);
+        set_account(&sender_id, &account);
+
+        removal.appeal = Some(Appeal {
+            asset: asset.clone(),
+            amount,
+            timestamp: env::block_timestamp().into()
+        });
+        get_removals().insert(&hierarchy_hash, &removal);
+        Event::log_appeal_content(hierarchies, asset, amount, None);
+        set_storage_usage(initial_storage_usage, Some(sender_id));
+    }
+
+    /// Restoring puts the content marker and its revision, like and reaction counts back,
+    /// refunds the stake and takes back the report drip.
+    /// Otherwise the stake is forfeited to the reporters.
+    pub fn resolve_appeal(&mut self, hierarchies: Vec<Hierarchy>, restore: bool) {
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = get_predecessor_id();
+        assert!(self.can_execute_action(None, None, Permission::ReportConfirm), "not allowed");
+
+        let hierarchy_hash = get_content_hash(hierarchies.clone(), None, true).unwrap();
+        let removal = get_removal(&hierarchy_hash).expect("removal not found");
+        let appeal = removal.appeal.clone().expect("not appealed");
+        assert!(self.get_user_mod_level(&sender_id) > removal.mod_level, "not allowed");
+        get_removals().remove(&hierarchy_hash);
+
+        let author_id = removal.hierarchies.last().unwrap().account_id.clone();
+        let mut drips = Vec::new();
+        let mut payouts = Vec::new();
+        if restore {
+            let key = get_marker_key(&hierarchy_hash);
+            assert!(!check(&key), "content exists");
+            set(&key, removal.reply_count);
+            post::restore_content_state(&hierarchy_hash, &removal.state);
+            let mut account = get_account(&author_id);
+            account.increase_balance(appeal.asset.clone(), appeal.amount.0);
+            set_account(&author_id, &account);
+            drips = self.drip.claw_back_drips(&removal.report_drips);
+            Event::log_restore_content(removal.hierarchies.clone(), None);
+        } else {
+            payouts = share_stake(&appeal.asset, appeal.amount.0, &removal.reporters);
+        }
+        Event::log_appeal_resolved(
+            removal.hierarchies,
+            restore,
+            sender_id,
+            Some(json!({
+                "drips": drips,
+                "payouts": payouts
+            }).to_string())
+        );
+        set_storage_usage(initial_storage_usage, None);
+    }
+
+    /// Drops removals whose appeal window closed without an appeal, anyone can call it
+    /// and the freed storage is refunded to the moderator who removed the content.
+    pub fn sweep_expired_removals(&mut self, contents: Vec<Vec<Hierarchy>>) {
+        for hierarchies in contents {
+            let initial_storage_usage = env::storage_usage();
+            let hierarchy_hash = match get_content_hash(hierarchies, None, true) {
+                Some(v) => v,
+                None => continue
+            };
+            let removal = match get_removal(&hierarchy_hash) {
+                Some(v) => v,
+                None => continue
+            };
+            if removal.appeal.is_some() || env::block_timestamp() <= removal.appeal_until.0 {
+                continue
+            }
+            get_removals().remove(&hierarchy_hash);
+            Event::log_other(
+                Some(json!({
+                    "expired_removal": removal.hierarchies
+                }).to_string())
+            );
+            let refund_id = match get_account(&removal.moderator).is_registered() {
+                true => removal.moderator,
+                false => env::current_account_id()
+            };
+            set_storage_usage(initial_storage_usage, Some(refund_id));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, str::FromStr};
+
+    use near_sdk::AccountId;
+
+    use crate::Community;
+    use crate::account::AssetKey;
+    use crate::post::{Hierarchy, Report, get_content_revision, get_like_count};
+    use crate::test_utils::{account, set_context, set_block_timestamp, setup_community};
+    use crate::utils::{self, set, set_content, get_stored_content_hash};
+    use super::{record_removal, get_removal, get_marker_key, APPEAL_WINDOW};
+
+    /// Alice's post, edited and liked by bob, reported by bob and carol and removed by a moderator.
+    fn setup_removal() -> (Community, Vec<Hierarchy>, String) {
+        let mut community = setup_community(HashMap::new());
+        let author = account("alice.testnet");
+        let moderator = account("mod.testnet");
+        community.role_management.roles.get("mod").unwrap().add_member(&moderator, &HashMap::new());
+        set_context(&author);
+        let target_hash = community.add_content("post".to_string(), vec![], None);
+        let hierarchies = vec![Hierarchy { target_hash, account_id: author, options: None }];
+        community.edit_content("post edited".to_string(), hierarchies.clone());
+        let hierarchy_hash = get_stored_content_hash(hierarchies.clone()).unwrap();
+        for reporter in ["bob.testnet", "carol.testnet"] {
+            set_context(&account(reporter));
+            community.internal_report(account(reporter), hierarchies.clone(), "spam".to_string());
+        }
+        set_context(&account("bob.testnet"));
+        community.like(hierarchies.clone());
+        set_context(&moderator);
+        community.report_confirm(hierarchies.clone(), Report::Approve);
+        (community, hierarchies, hierarchy_hash)
+    }
+
+    fn stake(community: &mut Community, hierarchies: &[Hierarchy], amount: u128) -> AssetKey {
+        let author = account("alice.testnet");
+        let asset = AssetKey::FT(account("ft.testnet"));
+        let mut account = utils::get_account(&author);
+        account.increase_balance(asset.clone(), amount);
+        utils::set_account(&author, &account);
+        set_context(&author);
+        community.appeal(hierarchies.to_vec(), asset.clone(), amount.into());
+        assert_eq!(utils::get_account(&author).get_balance(&asset), 0);
+        asset
+    }
+
+    #[test]
+    pub fn test_record_removal() {
+        let author = AccountId::from_str("alice.testnet").unwrap();
+        let moderator = AccountId::from_str("mod.testnet").unwrap();
+        let (target_hash, _, _) = set_content("post".to_string(), author.clone(), "".to_string(), None, None);
+        let hierarchies = vec![Hierarchy { target_hash, account_id: author, options: None }];
+        let hierarchy_hash = get_stored_content_hash(hierarchies.clone()).unwrap();
+        set(&get_marker_key(&hierarchy_hash), 3u8);
+
+        record_removal(&hierarchy_hash, hierarchies, moderator.clone(), 1, vec![], vec![]);
+        let removal = get_removal(&hierarchy_hash).unwrap();
+        assert_eq!(removal.reply_count, 3);
+        assert_eq!(removal.moderator, moderator);
+        assert!(removal.appeal.is_none());
+    }
+
+    #[test]
+    pub fn test_appeal_restore() {
+        let (mut community, hierarchies, hierarchy_hash) = setup_removal();
+        let removal = get_removal(&hierarchy_hash).unwrap();
+        let revision = removal.state.revision.as_ref().map(|v| (v.head, v.count.0));
+        assert!(revision.is_some());
+        assert_eq!(removal.state.like_count.0, 1);
+        assert!(get_stored_content_hash(hierarchies.clone()).is_none());
+        assert!(get_content_revision(&hierarchy_hash).is_none());
+        assert_eq!(get_like_count(&hierarchy_hash), 0);
+        let reporter_drip = utils::get_account(&account("bob.testnet")).get_drip();
+        assert!(utils::get_account(&account("carol.testnet")).get_drip() > 0);
+
+        let asset = stake(&mut community, &hierarchies, 100);
+        assert_eq!(get_removal(&hierarchy_hash).unwrap().appeal.unwrap().amount.0, 100);
+
+        set_context(&account("owner.testnet"));
+        community.resolve_appeal(hierarchies.clone(), true);
+        assert!(get_removal(&hierarchy_hash).is_none());
+        assert_eq!(get_stored_content_hash(hierarchies).unwrap(), hierarchy_hash);
+        assert_eq!(get_content_revision(&hierarchy_hash).map(|v| (v.head, v.count.0)), revision);
+        assert_eq!(get_like_count(&hierarchy_hash), 1);
+        assert_eq!(utils::get_account(&account("alice.testnet")).get_balance(&asset), 100);
+        assert!(utils::get_account(&account("bob.testnet")).get_drip() < reporter_drip);
+        assert_eq!(utils::get_account(&account("carol.testnet")).get_drip(), 0);
+    }
+
+    #[test]
+    pub fn test_appeal_forfeit() {
+        let (mut community, hierarchies, hierarchy_hash) = setup_removal();
+        let asset = stake(&mut community, &hierarchies, 101);
+
+        set_context(&account("owner.testnet"));
+        community.resolve_appeal(hierarchies.clone(), false);
+        assert!(get_removal(&hierarchy_hash).is_none());
+        assert!(get_stored_content_hash(hierarchies).is_none());
+        assert_eq!(utils::get_account(&account("bob.testnet")).get_balance(&asset), 50);
+        assert_eq!(utils::get_account(&account("carol.testnet")).get_balance(&asset), 50);
+        assert_eq!(utils::get_account(&account("community.testnet")).get_balance(&asset), 1);
+        assert_eq!(utils::get_account(&account("alice.testnet")).get_balance(&asset), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "appeal window closed")]
+    pub fn test_appeal_expired() {
+        let (mut community, hierarchies, _) = setup_removal();
+        set_block_timestamp(APPEAL_WINDOW + 1);
+        stake(&mut community, &hierarchies, 100);
+    }
+
+    #[test]
+    pub fn test_sweep_expired_removals() {
+        let (mut community, hierarchies, hierarchy_hash) = setup_removal();
+        community.sweep_expired_removals(vec![hierarchies.clone()]);
+        assert!(get_removal(&hierarchy_hash).is_some());
+
+        set_block_timestamp(APPEAL_WINDOW + 1);
+        community.sweep_expired_removals(vec![hierarchies]);
+        assert!(get_removal(&hierarchy_hash).is_none());
+    }
+}
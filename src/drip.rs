@@ -22,11 +22,11 @@ pub enum PendingDrip {
 #[derive(BorshDeserialize, BorshSerialize)]
 #[derive(Debug)]
 pub struct Drip {
-    accounts: LookupMap<AccountId, Account>,  
+    accounts: LookupMap<AccountId, OldAccount>,    //legacy accounts, see `utils::get_account`
 }
 
-pub fn get_map_value(key: &String) -> u128 {
-    let map: HashMap<String, U128> = serde_json::from_str(&json!({
+fn get_default_map() -> HashMap<String, U128> {
+    serde_json::from_str(&json!({
         "content0":   "1000000000000000000000000",    //post                       active
         "content1":   "1000000000000000000000000",    //comment                    active
         "content2":   "1000000000000000000000000",    //subcomment                 active
@@ -36,22 +36,140 @@ pub fn get_map_value(key: &String) -> u128 {
         "like":        "200000000000000000000000",         //like                       active
         "invite":     "10000000000000000000000000",        //invite                      active for inviter
         "be_liked":    "200000000000000000000000",     //be_liked                   passive
+        "react":       "200000000000000000000000",     //react, "react:<kind>" overrides  active
+        "be_reacted":  "200000000000000000000000",     //be_reacted, "be_reacted:<kind>" overrides  passive
+        "share":       "200000000000000000000000",     //share                      active
+        "be_shared":   "200000000000000000000000",     //be_shared                  passive
+        "share_referral": "100000000000000000000000",  //shared through            passive
         "report":     "1000000000000000000000000",      //report                     passive
         "report_deposit": "1000000000000000000000000",//report_deposit            passive
+        "report_confirm": "1000000000000000000000000", //report_confirm           active for moderator
         "be_voted":        "200000000000000000000000",  //be_voted                 passive
         "vote":        "200000000000000000000000"      //vote                     active
-    }).to_string()).unwrap();
-    let val = *map.get(key).unwrap_or(&(U128::from(0)));
+    }).to_string()).unwrap()
+}
+
+/// Reward table of this community, values set by owner or proposals override the defaults.
+pub fn get_drip_table() -> HashMap<String, U128> {
+    let table: LazyOption<HashMap<String, U128>> = LazyOption::new(StorageKey::DripTable, None);
+    let mut map = get_default_map();
+    if let Some(values) = table.get() {
+        map.extend(values);
+    }
+    map
+}
+
+pub(crate) fn set_drip_table(values: HashMap<String, U128>) {
+    let mut table: LazyOption<HashMap<String, U128>> = LazyOption::new(StorageKey::DripTable, None);
+    let mut map = table.get().unwrap_or_default();
+    map.extend(values);
+    table.set(&map);
+}
+
+pub fn get_map_value(key: &String) -> u128 {
+    let val = *get_drip_table().get(key).unwrap_or(&(U128::from(0)));
     val.0
 }
 
-fn get_content_decay(count: u8) -> u32 {
-    match count {
-        0 => 200,
-        _ => 100
+/// Reward key for adding content at `len`, an explicit "content_{len}" entry wins over the legacy "content{len}" keys.
+pub(crate) fn get_content_key(len: usize) -> String {
+    let key = format!("content_{}", len);
+    if get_drip_table().contains_key(&key) {
+        return key
+    }
+    format!("content{}", std::cmp::min(len, MAX_LEVEL - 1))
+}
+
+/// Reward key for the ancestor at level `i` when content is added at `len`. Without an explicit
+/// "content_{len}_{i}" entry, threads deeper than the default reward the direct parent with
+/// "content5" and every other ancestor with "content4".
+pub(crate) fn get_ancestor_key(len: usize, i: usize) -> String {
+    let key = format!("content_{}_{}", len, i);
+    if get_drip_table().contains_key(&key) {
+        return key
+    }
+    if len < MAX_LEVEL {
+        return "content".to_string() + &(i + MAX_LEVEL + len - 1).to_string()
+    }
+    if i == len - 1 { "content5".to_string() } else { "content4".to_string() }
+}
+
+pub const CONTENT_ACTION: &str = "content";
+pub const LIKE_ACTION: &str = "like";
+pub const SHARE_ACTION: &str = "share";
+pub const REACT_ACTION: &str = "react";
+
+/// "<key>:<kind>" when the table has a value for that reaction kind, "<key>" otherwise.
+fn get_reaction_key(key: &str, kind: &str) -> String {
+    let kind_key = format!("{}:{}", key, kind);
+    if get_drip_table().contains_key(&kind_key) { kind_key } else { key.to_string() }
+}
+
+/// Piecewise multiplier in percent, `tiers` are (inclusive upper count, percent) in ascending order.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[derive(Debug, Clone)]
+pub struct DecayCurve {
+    pub tiers: Vec<(U64, u32)>,
+    pub rest: u32      //above the last tier
+}
+
+impl DecayCurve {
+    pub fn get(&self, count: u64) -> u32 {
+        for (bound, per) in self.tiers.iter() {
+            if count <= bound.0 {
+                return *per
+            }
+        }
+        self.rest
     }
 }
 
+#[derive(BorshDeserialize, BorshSerialize)]
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[derive(Debug, Clone)]
+pub struct DecayConfig {
+    pub window: U64,                             //length of the rolling activity window
+    pub account: HashMap<String, DecayCurve>,    //action -> curve over the actions sent inside window
+    pub content: HashMap<u8, DecayCurve>,        //content level -> curve over the replies its parent already had
+}
+
+impl Default for DecayConfig {
+    fn default() -> Self {
+        let mut account = HashMap::new();
+        account.insert(CONTENT_ACTION.to_string(), DecayCurve { tiers: vec![(U64::from(10), 100)], rest: 40 });
+        let mut content = HashMap::new();
+        content.insert(1, DecayCurve { tiers: vec![(U64::from(0), 200)], rest: 100 });     //first comment to a post is doubled
+        Self {
+            window: U64::from(60 * 60 * 24 * 1000_000_000),
+            account,
+            content
+        }
+    }
+}
+
+impl DecayConfig {
+    pub fn get_account_decay(&self, action: &str, count: u64) -> u32 {
+        self.account.get(action).map(|curve| curve.get(count)).unwrap_or(100)
+    }
+
+    pub fn get_content_decay(&self, level: u8, count: u64) -> u32 {
+        self.content.get(&level).map(|curve| curve.get(count)).unwrap_or(100)
+    }
+}
+
+pub fn get_decay_config() -> DecayConfig {
+    let config: LazyOption<DecayConfig> = LazyOption::new(StorageKey::DecayConfig, None);
+    config.get().unwrap_or_default()
+}
+
+pub(crate) fn set_decay_config(config: &DecayConfig) {
+    let mut stored: LazyOption<DecayConfig> = LazyOption::new(StorageKey::DecayConfig, None);
+    stored.set(config);
+}
+
 impl Drip {
     pub fn new() -> Self {
         let mut this = Self { 
@@ -96,7 +214,32 @@ impl Drip {
         if let Some(new_total_drip) = total_drip.checked_add(drip) {
             account.increase_balance(asset, drip);
         }
-        self.accounts.insert(&env::current_account_id(), &account);
+        set_account(&env::current_account_id(), &account);
+    }
+
+    fn reduce_active_drip(&mut self, drip: u128) {
+        let asset = AssetKey::Drip((Some(AccountId::from_str("active").unwrap()), env::current_account_id()));
+        let mut account = get_account(&env::current_account_id());
+        let total_drip = account.get_balance(&asset);
+        account.decrease_balance(asset, std::cmp::min(total_drip, drip));
+        set_account(&env::current_account_id(), &account);
+    }
+
+    /// Takes back drip items paid earlier, an account can't go below zero.
+    /// Royalties were never counted as active drip, everything else is taken off the active total.
+    pub fn claw_back_drips(&mut self, items: &[(AccountId, String, U128)]) -> Vec<(AccountId, String, U128)> {
+        let mut drip_items = Vec::new();
+        for (account_id, key, amount) in items {
+            let mut account = get_account(account_id);
+            let drip = std::cmp::min(account.get_drip(), amount.0);
+            account.decrease_drip(drip);
+            set_account(account_id, &account);
+            if !key.ends_with(":royalty") {
+                self.reduce_active_drip(drip);
+            }
+            drip_items.push((account_id.clone(), key.clone() + ":clawback", drip.into()));
+        }
+        drip_items
     }
 
     pub fn set_custom_drip(&mut self, key: String, account_id: &AccountId, amount: u128, active_drip: bool) -> Vec<(AccountId, String, U128)> {
@@ -120,26 +263,24 @@ impl Drip {
             if hierarchy.account_id == account_id {
                 continue
             }
-            let key = "content".to_string() + &(i + MAX_LEVEL + len - 1).to_string();
+            let key = get_ancestor_key(len, i);
             let items = self.set_drip(key, hierarchy.options.clone(), &hierarchy.account_id, 100);
             drip_items = [drip_items, items].concat();
         }
 
-        let key = "content".to_string() + &(len).to_string();
+        let key = get_content_key(len);
         let mut per = 100;
-        // only comment can be doubled
         if let Some(prev_content_count) = prev_content_count{
-            if len == 1 {   
-                per = get_content_decay(prev_content_count);
-                let post = hierarchies.get(0).unwrap();
-                if per > 100 && post.account_id == account_id {
-                    per = 100
-                }
+            per = get_decay_config().get_content_decay(len as u8, prev_content_count as u64);
+            // no bonus for replying to yourself
+            let parent = hierarchies.get(len - 1).unwrap();
+            if per > 100 && parent.account_id == account_id {
+                per = 100
             }
         }
 
         let mut account = get_account(&account_id);
-        per = account.get_account_decay() * per / 100;
+        per = account.get_account_decay(CONTENT_ACTION) * per / 100;
         account.increase_content_count();
         set_account(&account_id, &account);
         
@@ -158,10 +299,58 @@ impl Drip {
         let key = "be_liked".to_string();
         let items = self.set_drip(key, hierarchy.options.clone(), &content_account_id, 100);
         drip_items = [drip_items, items].concat();
-        
+
+        let mut account = get_account(&account_id);
+        let per = account.get_account_decay(LIKE_ACTION);
+        account.increase_action_count(LIKE_ACTION);
+        set_account(&account_id, &account);
 
         let key = "like".to_string();
-        let items = self.set_drip(key, None, &account_id, 100);
+        let items = self.set_drip(key, None, &account_id, per);
+        [drip_items, items].concat()
+    }
+
+    pub fn set_react_drip(&mut self, hierarchies: Vec<Hierarchy>, account_id: AccountId, kind: &str) -> Vec<(AccountId, String, U128)> {
+        let hierarchy = hierarchies.get(hierarchies.len() - 1).unwrap();
+        let content_account_id = hierarchy.account_id.clone();
+        if content_account_id == account_id {
+            return vec![]
+        }
+
+        let key = get_reaction_key("be_reacted", kind);
+        let drip_items = self.set_drip(key, hierarchy.options.clone(), &content_account_id, 100);
+
+        let mut account = get_account(&account_id);
+        let per = account.get_account_decay(REACT_ACTION);
+        account.increase_action_count(REACT_ACTION);
+        set_account(&account_id, &account);
+
+        let key = get_reaction_key("react", kind);
+        let items = self.set_drip(key, None, &account_id, per);
+        [drip_items, items].concat()
+    }
+
+    pub fn set_share_drip(&mut self, hierarchies: Vec<Hierarchy>, account_id: AccountId, through: Option<AccountId>) -> Vec<(AccountId, String, U128)> {
+        let hierarchy = hierarchies.get(hierarchies.len() - 1).unwrap();
+        let content_account_id = hierarchy.account_id.clone();
+        if content_account_id == account_id {
+            return vec![]
+        }
+
+        let mut drip_items = self.set_drip("be_shared".to_string(), hierarchy.options.clone(), &content_account_id, 100);
+        if let Some(through) = through {
+            if through != account_id && through != content_account_id {
+                let items = self.set_drip("share_referral".to_string(), None, &through, 100);
+                drip_items = [drip_items, items].concat();
+            }
+        }
+
+        let mut account = get_account(&account_id);
+        let per = account.get_account_decay(SHARE_ACTION);
+        account.increase_action_count(SHARE_ACTION);
+        set_account(&account_id, &account);
+
+        let items = self.set_drip("share".to_string(), None, &account_id, per);
         [drip_items, items].concat()
     }
 
@@ -210,7 +399,7 @@ impl Drip {
         let mut to_account = get_account(&to).registered();
         let amount = from_account.get_drip();
         from_account.decrease_drip(amount);
-        self.accounts.insert(&from, &from_account);
+        set_account(&from, &from_account);
         to_account.increase_drip(amount);
         set_account(&to, &to_account);
         vec![(to, "gather".to_string(), amount.into())]
@@ -244,7 +433,7 @@ impl Drip {
         if let Some(new_total_drip) = total_drip.checked_add(balance) {
             account.increase_balance(asset, balance);
         }
-        self.accounts.insert(&account_id, &account);
+        set_account(&account_id, &account);
         balance.into()
     }
 
@@ -253,13 +442,13 @@ impl Drip {
         account.get_drip().into()
     }
 
-    pub fn get_account_decay(&self, account_id: AccountId) -> u32 {
+    pub fn get_account_decay(&self, account_id: AccountId, action: &str) -> u32 {
         let account = get_account(&account_id);
-        account.get_account_decay()
+        account.get_account_decay(action)
     }
 
-    pub fn get_content_decay(&self, content_count: u32) -> u32 {
-        get_content_decay(content_count as u8)
+    pub fn get_content_decay(&self, level: u8, content_count: u32) -> u32 {
+        get_decay_config().get_content_decay(level, content_count as u64)
     }
 }
 
@@ -282,8 +471,9 @@ mod test {
     use near_sdk::{json_types::U128, AccountId, serde_json::json, serde_json, env};
 
     use crate::account::{self, Account};
+    use crate::utils::{get_account, set_account};
 
-    use super::{U256, get_map_value, Drip};
+    use super::{U256, get_map_value, set_drip_table, DecayConfig, Drip, get_content_key, get_ancestor_key};
 
 
     #[test]
@@ -312,16 +502,56 @@ mod test {
         println!("{:?}", drip);
     }
 
+    #[test]
+    pub fn test_drip_table() {
+        assert_eq!(get_map_value(&"report_confirm".to_string()), 1000000000000000000000000);
+        let mut values = HashMap::new();
+        values.insert("like".to_string(), U128::from(5));
+        set_drip_table(values);
+        assert_eq!(get_map_value(&"like".to_string()), 5);
+        assert_eq!(get_map_value(&"be_liked".to_string()), 200000000000000000000000);
+    }
+
+    #[test]
+    pub fn test_content_keys() {
+        assert_eq!(get_content_key(0), "content0");
+        assert_eq!(get_content_key(2), "content2");
+        assert_eq!(get_content_key(4), "content2");
+        assert_eq!(get_ancestor_key(1, 0), "content3");
+        assert_eq!(get_ancestor_key(2, 0), "content4");
+        assert_eq!(get_ancestor_key(2, 1), "content5");
+        assert_eq!(get_ancestor_key(4, 0), "content4");
+        assert_eq!(get_ancestor_key(4, 3), "content5");
+        let mut values = HashMap::new();
+        values.insert("content_4".to_string(), U128::from(5));
+        values.insert("content_4_0".to_string(), U128::from(1));
+        set_drip_table(values);
+        assert_eq!(get_content_key(4), "content_4");
+        assert_eq!(get_ancestor_key(4, 0), "content_4_0");
+        assert_eq!(get_ancestor_key(4, 1), "content4");
+    }
+
+    #[test]
+    pub fn test_decay_config() {
+        let config = DecayConfig::default();
+        assert_eq!(config.get_account_decay("content", 10), 100);
+        assert_eq!(config.get_account_decay("content", 11), 40);
+        assert_eq!(config.get_account_decay("like", 100), 100);
+        assert_eq!(config.get_content_decay(1, 0), 200);
+        assert_eq!(config.get_content_decay(1, 1), 100);
+        assert_eq!(config.get_content_decay(2, 0), 100);
+    }
+
     #[test]
     pub fn decay() {
         print!("{:?}", env::block_timestamp());
         let account_id = AccountId::from_str("gugu2029.testnet").unwrap();
         let mut account = Account::new(&account_id);
-        account.data.insert("content_count".to_string(), "13".to_string());
-        account.data.insert("one_day_timestamp".to_string(), "1697013468067413865".to_string());
+        account.activity.action_counts.insert("content".to_string(), 13);
+        account.activity.window_timestamp = 1697013468067413865;
         let mut drip = Drip::new();
-        drip.accounts.insert(&account_id, &account);
+        set_account(&account_id, &account);
         drip.set_content_drip(vec![], account_id.clone(), None);
-        println!("{:?}", drip.accounts.get(&account_id).unwrap())
+        println!("{:?}", get_account(&account_id))
     }
 }
\ No newline at end of file
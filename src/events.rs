@@ -1,6 +1,7 @@
 use std::fmt::Display;
 use crate::*;
-use post::Hierarchy;
+use post::{Hierarchy, Report};
+use account::AssetKey;
 
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -8,10 +9,32 @@ use post::Hierarchy;
 pub enum Event {
     Follow(Vec<FollowData>),
     Unfollow(Vec<FollowData>),
+    FollowRequest(Vec<FollowData>),
+    FollowAccept(Vec<FollowData>),
+    FollowReject(Vec<FollowData>),
+    FollowCancel(Vec<FollowData>),
+    AccountPrivate(Vec<AccountPrivateData>),
+    Block(Vec<AccountRelationData>),
+    Unblock(Vec<AccountRelationData>),
+    Mute(Vec<AccountRelationData>),
+    Unmute(Vec<AccountRelationData>),
     ContentAdd(Vec<ContentAddData>),
     ContentDel(Vec<ContentHierarchyData>),
     ContentLike(Vec<ContentHierarchyData>),
     ContentUnlike(Vec<ContentHierarchyData>),
+    ContentEdit(Vec<ContentEditData>),
+    ContentShare(Vec<ContentShareData>),
+    ContentPin(Vec<ContentHierarchyData>),
+    ContentUnpin(Vec<ContentHierarchyData>),
+    ContentReact(Vec<ContentReactData>),
+    ContentUnreact(Vec<ContentReactData>),
+    ContentReport(Vec<ContentReportData>),
+    ContentHide(Vec<ContentHierarchyData>),
+    ContentUnhide(Vec<ContentHierarchyData>),
+    ReportResolved(Vec<ReportResolvedData>),
+    ContentAppeal(Vec<ContentAppealData>),
+    AppealResolved(Vec<AppealResolvedData>),
+    ContentRestore(Vec<ContentHierarchyData>),
 
     //custome events
     Invite(Vec<InviteData>),
@@ -40,6 +63,22 @@ pub struct FollowData {
     pub memo: Option<String>
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountPrivateData {
+    pub account_id: AccountId,
+    pub private: bool,
+    pub memo: Option<String>
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountRelationData {
+    pub account_id: AccountId,
+    pub target_id: AccountId,
+    pub memo: Option<String>
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(crate = "near_sdk::serde")]
 pub struct ContentAddData {
@@ -48,6 +87,70 @@ pub struct ContentAddData {
     pub memo: Option<String>
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContentEditData {
+    pub args: String,
+    pub hierarchies: Vec<Hierarchy>,
+    pub timestamp: U64,
+    pub prev_revision: Base58CryptoHash,
+    pub revision: Base58CryptoHash,
+    pub memo: Option<String>
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContentShareData {
+    pub hierarchies: Vec<Hierarchy>,
+    pub through: Option<AccountId>,
+    pub memo: Option<String>
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContentReactData {
+    pub hierarchies: Vec<Hierarchy>,
+    pub reaction: String,
+    pub previous: Option<String>,
+    pub memo: Option<String>
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContentReportData {
+    pub hierarchies: Vec<Hierarchy>,
+    pub reporter: AccountId,
+    pub reason: String,
+    pub memo: Option<String>
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ReportResolvedData {
+    pub hierarchies: Vec<Hierarchy>,
+    pub result: Report,
+    pub reporters: Vec<AccountId>,
+    pub memo: Option<String>
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContentAppealData {
+    pub hierarchies: Vec<Hierarchy>,
+    pub asset: AssetKey,
+    pub amount: U128,
+    pub memo: Option<String>
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AppealResolvedData {
+    pub hierarchies: Vec<Hierarchy>,
+    pub restored: bool,
+    pub moderator: AccountId,
+    pub memo: Option<String>
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(crate = "near_sdk::serde")]
 pub struct ContentHierarchyData {
@@ -99,6 +202,96 @@ impl Event {
         ]).log()
     }
 
+    pub fn log_follow_request(follower: AccountId, followee: AccountId, memo: Option<String>) {
+        Event::FollowRequest(vec![
+            FollowData {
+                follower,
+                followee,
+                memo
+            }
+        ]).log()
+    }
+
+    pub fn log_follow_accept(follower: AccountId, followee: AccountId, memo: Option<String>) {
+        Event::FollowAccept(vec![
+            FollowData {
+                follower,
+                followee,
+                memo
+            }
+        ]).log()
+    }
+
+    pub fn log_follow_reject(follower: AccountId, followee: AccountId, memo: Option<String>) {
+        Event::FollowReject(vec![
+            FollowData {
+                follower,
+                followee,
+                memo
+            }
+        ]).log()
+    }
+
+    pub fn log_follow_cancel(follower: AccountId, followee: AccountId, memo: Option<String>) {
+        Event::FollowCancel(vec![
+            FollowData {
+                follower,
+                followee,
+                memo
+            }
+        ]).log()
+    }
+
+    pub fn log_account_private(account_id: AccountId, private: bool, memo: Option<String>) {
+        Event::AccountPrivate(vec![
+            AccountPrivateData {
+                account_id,
+                private,
+                memo
+            }
+        ]).log()
+    }
+
+    pub fn log_block(account_id: AccountId, target_id: AccountId, memo: Option<String>) {
+        Event::Block(vec![
+            AccountRelationData {
+                account_id,
+                target_id,
+                memo
+            }
+        ]).log()
+    }
+
+    pub fn log_unblock(account_id: AccountId, target_id: AccountId, memo: Option<String>) {
+        Event::Unblock(vec![
+            AccountRelationData {
+                account_id,
+                target_id,
+                memo
+            }
+        ]).log()
+    }
+
+    pub fn log_mute(account_id: AccountId, target_id: AccountId, memo: Option<String>) {
+        Event::Mute(vec![
+            AccountRelationData {
+                account_id,
+                target_id,
+                memo
+            }
+        ]).log()
+    }
+
+    pub fn log_unmute(account_id: AccountId, target_id: AccountId, memo: Option<String>) {
+        Event::Unmute(vec![
+            AccountRelationData {
+                account_id,
+                target_id,
+                memo
+            }
+        ]).log()
+    }
+
     pub fn log_add_content(args: String, hierarchies: Vec<Hierarchy>, memo: Option<String>) {
         Event::ContentAdd(vec![
             ContentAddData {
@@ -109,6 +302,19 @@ impl Event {
         ]).log()
     }
 
+    pub fn log_edit_content(args: String, hierarchies: Vec<Hierarchy>, prev_revision: Base58CryptoHash, revision: Base58CryptoHash, memo: Option<String>) {
+        Event::ContentEdit(vec![
+            ContentEditData {
+                args,
+                hierarchies,
+                timestamp: env::block_timestamp().into(),
+                prev_revision,
+                revision,
+                memo
+            }
+        ]).log()
+    }
+
     pub fn log_del_content(hierarchies: Vec<Hierarchy>, memo: Option<String>) {
         Event::ContentDel(vec![
             ContentHierarchyData {
@@ -128,7 +334,7 @@ impl Event {
     }
 
     pub fn log_unlike_content(hierarchies: Vec<Hierarchy>, memo: Option<String>) {
-        Event::ContentLike(vec![
+        Event::ContentUnlike(vec![
             ContentHierarchyData {
                 hierarchies,
                 memo
@@ -136,6 +342,56 @@ impl Event {
         ]).log()
     }
 
+    pub fn log_share_content(hierarchies: Vec<Hierarchy>, through: Option<AccountId>, memo: Option<String>) {
+        Event::ContentShare(vec![
+            ContentShareData {
+                hierarchies,
+                through,
+                memo
+            }
+        ]).log()
+    }
+
+    pub fn log_pin_content(hierarchies: Vec<Hierarchy>, memo: Option<String>) {
+        Event::ContentPin(vec![
+            ContentHierarchyData {
+                hierarchies,
+                memo
+            }
+        ]).log()
+    }
+
+    pub fn log_unpin_content(hierarchies: Vec<Hierarchy>, memo: Option<String>) {
+        Event::ContentUnpin(vec![
+            ContentHierarchyData {
+                hierarchies,
+                memo
+            }
+        ]).log()
+    }
+
+    pub fn log_react_content(hierarchies: Vec<Hierarchy>, reaction: String, previous: Option<String>, memo: Option<String>) {
+        Event::ContentReact(vec![
+            ContentReactData {
+                hierarchies,
+                reaction,
+                previous,
+                memo
+            }
+        ]).log()
+    }
+
+    pub fn log_unreact_content(hierarchies: Vec<Hierarchy>, reaction: String, memo: Option<String>) {
+        Event::ContentUnreact(vec![
+            ContentReactData {
+                hierarchies,
+                reaction,
+                previous: None,
+                memo
+            }
+        ]).log()
+    }
+
     pub fn log_invite(inviter_id: AccountId, invitee_id: AccountId, memo: Option<String>) {
         Event::Invite(vec![
             InviteData {
@@ -150,6 +406,77 @@ impl Event {
         Event::SetMetadata(metadata).log()
     }
 
+    pub fn log_report_content(hierarchies: Vec<Hierarchy>, reporter: AccountId, reason: String, memo: Option<String>) {
+        Event::ContentReport(vec![
+            ContentReportData {
+                hierarchies,
+                reporter,
+                reason,
+                memo
+            }
+        ]).log()
+    }
+
+    pub fn log_hide_content(hierarchies: Vec<Hierarchy>, memo: Option<String>) {
+        Event::ContentHide(vec![
+            ContentHierarchyData {
+                hierarchies,
+                memo
+            }
+        ]).log()
+    }
+
+    pub fn log_unhide_content(hierarchies: Vec<Hierarchy>, memo: Option<String>) {
+        Event::ContentUnhide(vec![
+            ContentHierarchyData {
+                hierarchies,
+                memo
+            }
+        ]).log()
+    }
+
+    pub fn log_report_resolved(hierarchies: Vec<Hierarchy>, result: Report, reporters: Vec<AccountId>, memo: Option<String>) {
+        Event::ReportResolved(vec![
+            ReportResolvedData {
+                hierarchies,
+                result,
+                reporters,
+                memo
+            }
+        ]).log()
+    }
+
+    pub fn log_appeal_content(hierarchies: Vec<Hierarchy>, asset: AssetKey, amount: U128, memo: Option<String>) {
+        Event::ContentAppeal(vec![
+            ContentAppealData {
+                hierarchies,
+                asset,
+                amount,
+                memo
+            }
+        ]).log()
+    }
+
+    pub fn log_appeal_resolved(hierarchies: Vec<Hierarchy>, restored: bool, moderator: AccountId, memo: Option<String>) {
+        Event::AppealResolved(vec![
+            AppealResolvedData {
+                hierarchies,
+                restored,
+                moderator,
+                memo
+            }
+        ]).log()
+    }
+
+    pub fn log_restore_content(hierarchies: Vec<Hierarchy>, memo: Option<String>) {
+        Event::ContentRestore(vec![
+            ContentHierarchyData {
+                hierarchies,
+                memo
+            }
+        ]).log()
+    }
+
     pub fn log_other(memo: Option<String>) {
         Event::Other(vec![
             Data {
@@ -0,0 +1,431 @@
+use crate::*;
+use post::Hierarchy;
+use utils::check;
+
+#[derive(BorshDeserialize, BorshSerialize)]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FollowCount {
+    pub followers: u64,
+    pub following: u64
+}
+
+/// Relationship of `account_id` towards `target_id`, and the other way round.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[derive(Debug, Clone)]
+pub struct AccountRelation {
+    pub following: bool,
+    pub followed_by: bool,
+    pub blocking: bool,
+    pub blocked_by: bool,
+    pub muting: bool,
+    pub muted_by: bool,
+    pub requested: bool,
+    pub requested_by: bool
+}
+
+fn get_follow_counts() -> LookupMap<AccountId, FollowCount> {
+    LookupMap::new(StorageKey::FollowCounts)
+}
+
+pub fn get_follow_count(account_id: &AccountId) -> FollowCount {
+    get_follow_counts().get(account_id).unwrap_or_default()
+}
+
+fn set_follow_count(account_id: &AccountId, count: &FollowCount) {
+    if *count == FollowCount::default() {
+        get_follow_counts().remove(account_id);
+    } else {
+        get_follow_counts().insert(account_id, count);
+    }
+}
+
+// follows written before the counters existed are not counted, so decreasing saturates
+fn update_follow_count(follower: &AccountId, followee: &AccountId, increase: bool) {
+    let mut count = get_follow_count(follower);
+    count.following = if increase { count.following + 1 } else { count.following.saturating_sub(1) };
+    set_follow_count(follower, &count);
+    let mut count = get_follow_count(followee);
+    count.followers = if increase { count.followers + 1 } else { count.followers.saturating_sub(1) };
+    set_follow_count(followee, &count);
+}
+
+fn get_following_key(follower: &AccountId, followee: &AccountId) -> Vec<u8> {
+    env::sha256(&(follower.to_string() + "follwing" + followee.as_str()).into_bytes())
+}
+
+fn get_block_key(account_id: &AccountId, target_id: &AccountId) -> Vec<u8> {
+    env::sha256(&(account_id.to_string() + "block" + target_id.as_str()).into_bytes())
+}
+
+fn get_mute_key(account_id: &AccountId, target_id: &AccountId) -> Vec<u8> {
+    env::sha256(&(account_id.to_string() + "mute" + target_id.as_str()).into_bytes())
+}
+
+fn get_private_key(account_id: &AccountId) -> Vec<u8> {
+    env::sha256(&(account_id.to_string() + "private").into_bytes())
+}
+
+fn get_request_key(follower: &AccountId, followee: &AccountId) -> Vec<u8> {
+    env::sha256(&(follower.to_string() + "follow_request" + followee.as_str()).into_bytes())
+}
+
+/// A page of pending follow requests, `next` is the queue index to continue from, None once the end is reached.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FollowRequestPage {
+    pub requests: Vec<AccountId>,
+    pub next: Option<U64>
+}
+
+/// Pending follow requests in arrival order, resolved requests leave an empty slot
+/// until every request before them is resolved too.
+struct RequestQueue {
+    prefix: Vec<u8>
+}
+
+impl RequestQueue {
+    fn head_key(&self) -> Vec<u8> {
+        [self.prefix.clone(), b"_head".to_vec()].concat()
+    }
+
+    fn tail_key(&self) -> Vec<u8> {
+        [self.prefix.clone(), b"_tail".to_vec()].concat()
+    }
+
+    fn read(key: &[u8]) -> u64 {
+        env::storage_read(key).map_or(0, |v| u64::try_from_slice(&v).unwrap())
+    }
+
+    fn write(key: &[u8], value: u64) {
+        if value == 0 {
+            env::storage_remove(key);
+        } else {
+            env::storage_write(key, &value.try_to_vec().unwrap());
+        }
+    }
+
+    fn accounts(&self) -> LookupMap<u64, AccountId> {
+        LookupMap::new([self.prefix.clone(), b"_idx".to_vec()].concat())
+    }
+
+    fn positions(&self) -> LookupMap<AccountId, u64> {
+        LookupMap::new([self.prefix.clone(), b"_pos".to_vec()].concat())
+    }
+
+    fn push(&self, account_id: &AccountId) {
+        if self.positions().contains_key(account_id) {
+            return
+        }
+        let tail = Self::read(&self.tail_key());
+        self.accounts().insert(&tail, account_id);
+        self.positions().insert(account_id, &tail);
+        Self::write(&self.tail_key(), tail + 1);
+    }
+
+    fn remove(&self, account_id: &AccountId) {
+        let index = match self.positions().remove(account_id) {
+            Some(index) => index,
+            None => return
+        };
+        let mut accounts = self.accounts();
+        accounts.remove(&index);
+        let mut head = Self::read(&self.head_key());
+        if index != head {
+            return
+        }
+        let tail = Self::read(&self.tail_key());
+        while head < tail && !accounts.contains_key(&head) {
+            head += 1;
+        }
+        if head == tail {
+            Self::write(&self.head_key(), 0);
+            Self::write(&self.tail_key(), 0);
+        } else {
+            Self::write(&self.head_key(), head);
+        }
+    }
+
+    /// Reads `limit` queue slots from index `from`, so a page may hold fewer requests than `limit` where requests were resolved.
+    fn get(&self, from: u64, limit: u64) -> FollowRequestPage {
+        let accounts = self.accounts();
+        let tail = Self::read(&self.tail_key());
+        let start = std::cmp::max(from, Self::read(&self.head_key()));
+        let end = std::cmp::min(start.saturating_add(limit), tail);
+        FollowRequestPage {
+            requests: (start..end).filter_map(|index| accounts.get(&index)).collect(),
+            next: if end < tail { Some(end.into()) } else { None }
+        }
+    }
+}
+
+fn get_request_queue(account_id: &AccountId) -> RequestQueue {
+    RequestQueue {
+        prefix: env::sha256(&(account_id.to_string() + "follow_requests").into_bytes())[0..16].to_vec()
+    }
+}
+
+pub fn get_follow_requests(account_id: &AccountId, from: u64, limit: u64) -> FollowRequestPage {
+    get_request_queue(account_id).get(from, limit)
+}
+
+pub fn is_private(account_id: &AccountId) -> bool {
+    check(&get_private_key(account_id))
+}
+
+pub fn is_requested(follower: &AccountId, followee: &AccountId) -> bool {
+    check(&get_request_key(follower, followee))
+}
+
+pub fn is_following(follower: &AccountId, followee: &AccountId) -> bool {
+    check(&get_following_key(follower, followee))
+}
+
+pub fn is_blocked(account_id: &AccountId, target_id: &AccountId) -> bool {
+    check(&get_block_key(account_id, target_id))
+}
+
+pub fn is_muted(account_id: &AccountId, target_id: &AccountId) -> bool {
+    check(&get_mute_key(account_id, target_id))
+}
+
+pub fn get_relation(account_id: &AccountId, target_id: &AccountId) -> AccountRelation {
+    AccountRelation {
+        following: is_following(account_id, target_id),
+        followed_by: is_following(target_id, account_id),
+        blocking: is_blocked(account_id, target_id),
+        blocked_by: is_blocked(target_id, account_id),
+        muting: is_muted(account_id, target_id),
+        muted_by: is_muted(target_id, account_id),
+        requested: is_requested(account_id, target_id),
+        requested_by: is_requested(target_id, account_id)
+    }
+}
+
+/// Panics if any author along the hierarchies has blocked `account_id`.
+pub(crate) fn assert_not_blocked(hierarchies: &[Hierarchy], account_id: &AccountId) {
+    for hierarchy in hierarchies {
+        assert!(!is_blocked(&hierarchy.account_id, account_id), "blocked");
+    }
+}
+
+/// Follow, block and mute markers are paid from the signer's storage like content, so only registered accounts write them.
+fn assert_registered() {
+    assert!(get_account(&env::signer_account_id()).is_registered(), "not registered");
+}
+
+fn internal_follow(follower: &AccountId, followee: &AccountId) {
+    let hash = get_following_key(follower, followee);
+    if !check(&hash) {
+        set(&hash, 0);
+        update_follow_count(follower, followee, true);
+    }
+    Event::log_follow(follower.clone(), followee.clone(), None);
+}
+
+/// Drops a pending request, returns false if there was none.
+fn remove_request(follower: &AccountId, followee: &AccountId) -> bool {
+    let hash = get_request_key(follower, followee);
+    if !check(&hash) {
+        return false
+    }
+    remove(&hash);
+    get_request_queue(followee).remove(follower);
+    true
+}
+
+fn internal_unfollow(follower: &AccountId, followee: &AccountId) -> bool {
+    let hash = get_following_key(follower, followee);
+    if !check(&hash) {
+        return false
+    }
+    remove(&hash);
+    update_follow_count(follower, followee, false);
+    Event::log_unfollow(follower.clone(), followee.clone(), None);
+    true
+}
+
+#[near_bindgen]
+impl Community {
+    pub fn follow(&mut self, account_id: AccountId) {
+        init_callback();
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = get_predecessor_id();
+        assert_registered();
+        assert!(sender_id != account_id, "can not follow self");
+        assert!(!is_blocked(&account_id, &sender_id), "blocked");
+        if is_private(&account_id) && !is_following(&sender_id, &account_id) {
+            let hash = get_request_key(&sender_id, &account_id);
+            if !check(&hash) {
+                set(&hash, 0);
+                get_request_queue(&account_id).push(&sender_id);
+                Event::log_follow_request(sender_id, account_id, None);
+            }
+        } else {
+            internal_follow(&sender_id, &account_id);
+        }
+        set_storage_usage(initial_storage_usage, None);
+    }
+
+    /// Private accounts only get followers through accepted follow requests.
+    /// Requests already pending stay pending after the account goes public.
+    pub fn set_private(&mut self, private: bool) {
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = get_predecessor_id();
+        assert_registered();
+        if private {
+            set(&get_private_key(&sender_id), 0);
+        } else {
+            remove(&get_private_key(&sender_id));
+        }
+        Event::log_account_private(sender_id, private, None);
+        set_storage_usage(initial_storage_usage, None);
+    }
+
+    pub fn accept_follow_request(&mut self, account_id: AccountId) {
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = get_predecessor_id();
+        assert_registered();
+        assert!(remove_request(&account_id, &sender_id), "request not found");
+        Event::log_follow_accept(account_id.clone(), sender_id.clone(), None);
+        internal_follow(&account_id, &sender_id);
+        set_storage_usage(initial_storage_usage, None);
+    }
+
+    pub fn reject_follow_request(&mut self, account_id: AccountId) {
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = get_predecessor_id();
+        assert!(remove_request(&account_id, &sender_id), "request not found");
+        Event::log_follow_reject(account_id, sender_id, None);
+        set_storage_usage(initial_storage_usage, None);
+    }
+
+    pub fn cancel_follow_request(&mut self, account_id: AccountId) {
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = get_predecessor_id();
+        assert!(remove_request(&sender_id, &account_id), "request not found");
+        Event::log_follow_cancel(sender_id, account_id, None);
+        set_storage_usage(initial_storage_usage, None);
+    }
+
+    pub fn unfollow(&mut self, account_id: AccountId) {
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = get_predecessor_id();
+        if !internal_unfollow(&sender_id, &account_id) {
+            Event::log_unfollow(sender_id, account_id, None);
+        }
+        set_storage_usage(initial_storage_usage, None);
+    }
+
+    /// Blocked accounts can't follow, reply to, like or react to the sender's content.
+    /// Follows between the two accounts are removed.
+    pub fn block_account(&mut self, account_id: AccountId) {
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = get_predecessor_id();
+        assert_registered();
+        assert!(sender_id != account_id, "can not block self");
+        set(&get_block_key(&sender_id, &account_id), 0);
+        if remove_request(&account_id, &sender_id) {
+            Event::log_follow_reject(account_id.clone(), sender_id.clone(), None);
+        }
+        internal_unfollow(&account_id, &sender_id);
+        internal_unfollow(&sender_id, &account_id);
+        Event::log_block(sender_id, account_id, None);
+        set_storage_usage(initial_storage_usage, None);
+    }
+
+    pub fn unblock_account(&mut self, account_id: AccountId) {
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = get_predecessor_id();
+        remove(&get_block_key(&sender_id, &account_id));
+        Event::log_unblock(sender_id, account_id, None);
+        set_storage_usage(initial_storage_usage, None);
+    }
+
+    /// Muting is only recorded for clients to filter the sender's feed, nothing is enforced.
+    pub fn mute_account(&mut self, account_id: AccountId) {
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = get_predecessor_id();
+        assert_registered();
+        assert!(sender_id != account_id, "can not mute self");
+        set(&get_mute_key(&sender_id, &account_id), 0);
+        Event::log_mute(sender_id, account_id, None);
+        set_storage_usage(initial_storage_usage, None);
+    }
+
+    pub fn unmute_account(&mut self, account_id: AccountId) {
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = get_predecessor_id();
+        remove(&get_mute_key(&sender_id, &account_id));
+        Event::log_unmute(sender_id, account_id, None);
+        set_storage_usage(initial_storage_usage, None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, str::FromStr};
+
+    use near_sdk::{AccountId, env};
+
+    use crate::AccessLimit;
+    use crate::test_utils::{account, set_context, setup_community};
+
+    use super::{update_follow_count, get_follow_count, FollowCount, get_follow_requests, FollowRequestPage, get_request_key, get_request_queue, remove_request};
+    use crate::utils::{set, check};
+
+    #[test]
+    pub fn test_follow_count() {
+        let alice = AccountId::from_str("alice.testnet").unwrap();
+        let bob = AccountId::from_str("bob.testnet").unwrap();
+        update_follow_count(&alice, &bob, true);
+        update_follow_count(&bob, &alice, true);
+        update_follow_count(&alice, &bob, false);
+        assert_eq!(get_follow_count(&alice), FollowCount { followers: 1, following: 0 });
+        assert_eq!(get_follow_count(&bob), FollowCount { followers: 0, following: 1 });
+        update_follow_count(&bob, &alice, false);
+        update_follow_count(&bob, &alice, false);
+        assert_eq!(get_follow_count(&alice), FollowCount::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "not registered")]
+    pub fn test_follow_unregistered() {
+        let mut community = setup_community(HashMap::new());
+        community.access = AccessLimit::Registry;
+        env::state_write(&community);
+        set_context(&account("stranger.testnet"));
+        community.mute_account(account("alice.testnet"));
+    }
+
+    #[test]
+    pub fn test_follow_requests() {
+        let alice = AccountId::from_str("alice.testnet").unwrap();
+        let bob = AccountId::from_str("bob.testnet").unwrap();
+        let carol = AccountId::from_str("carol.testnet").unwrap();
+        let dave = AccountId::from_str("dave.testnet").unwrap();
+        for follower in [&bob, &carol, &dave] {
+            set(&get_request_key(follower, &alice), 0);
+            get_request_queue(&alice).push(follower);
+        }
+        assert!(remove_request(&bob, &alice));
+        assert!(!remove_request(&bob, &alice));
+        assert!(!check(&get_request_key(&bob, &alice)));
+        assert_eq!(get_follow_requests(&alice, 0, 10).requests, vec![carol.clone(), dave.clone()]);
+
+        // resolving a request in the middle keeps the rest in arrival order
+        set(&get_request_key(&bob, &alice), 0);
+        get_request_queue(&alice).push(&bob);
+        assert!(remove_request(&dave, &alice));
+        assert_eq!(get_follow_requests(&alice, 0, 10), FollowRequestPage { requests: vec![carol.clone(), bob.clone()], next: None });
+
+        // pages count queue slots, so the hole left by dave is read once and passed
+        let page = get_follow_requests(&alice, 0, 2);
+        assert_eq!(page, FollowRequestPage { requests: vec![carol.clone()], next: Some(3.into()) });
+        assert_eq!(get_follow_requests(&alice, page.next.unwrap().0, 2), FollowRequestPage { requests: vec![bob.clone()], next: None });
+        assert!(remove_request(&carol, &alice));
+        assert!(remove_request(&bob, &alice));
+        assert!(get_follow_requests(&alice, 0, 10).requests.is_empty());
+    }
+}
@@ -1,6 +1,7 @@
-use crate::utils::get_root_id;
+use crate::utils::{get_root_id, get, init_callback, set_content};
 use crate::{utils::get_content_hash};
 use crate::*;
+use post::get_max_depth;
 
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(crate = "near_sdk::serde")]
@@ -11,7 +12,51 @@ pub enum Instruction {
 }
 
 impl Community {
-    pub(crate) fn internal_report(&mut self, sender_id: AccountId, hierarchies: Vec<Hierarchy>) {
+    pub(crate) fn internal_add_content(&mut self, args: String, hierarchies: Vec<Hierarchy>, options: Option<HashMap<String, String>>, permission: Permission) -> Base58CryptoHash {
+        init_callback();
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = get_predecessor_id();
+
+        assert!(self.can_execute_action(None, None, permission), "not allowed");
+        post::check_schedule(&options);
+
+        assert!(hierarchies.len() < get_max_depth(), "error");
+        follow::assert_not_blocked(&hierarchies, &sender_id);
+
+        let hash_prefix = get_content_hash(hierarchies.clone(), None, false).expect("content not found");
+        report::assert_not_hidden(&hash_prefix);
+        let (target_hash, nonce, mmr_index) = set_content(args.clone(), sender_id.clone(), hash_prefix.clone(), options.clone(), None);
+
+        let mut prev_content_count = None;
+        if hierarchies.len() > 0 {
+            let prev_hash = CryptoHash::from(Base58CryptoHash::try_from(hash_prefix.clone()).unwrap()).to_vec();
+            let mut val: u8 = get(&prev_hash).unwrap();
+            prev_content_count = Some(val.clone());
+            // reply count feeds the content decay curve
+            val = val.saturating_add(1);
+            set(&prev_hash, val)
+        }
+
+        let drips = self.drip.set_content_drip(hierarchies.clone(), sender_id.clone(), prev_content_count);
+        Event::log_add_content(
+            args, 
+            [hierarchies, vec![Hierarchy { 
+                target_hash, 
+                account_id: sender_id,
+                options
+            }]].concat(),
+            Some(json!({
+                "drips": drips,
+                "nonce": nonce,
+                "mmr_index": U64::from(mmr_index),
+                "mmr_root": mmr::get_root(None).root
+            }).to_string())
+        );
+        set_storage_usage(initial_storage_usage, None);
+        target_hash
+    }
+
+    pub(crate) fn internal_report(&mut self, sender_id: AccountId, hierarchies: Vec<Hierarchy>, reason: String) {
         let initial_storage_usage = env::storage_usage();
 
         assert!(self.can_execute_action(None, None, Permission::Report), "not allowed");
@@ -23,11 +68,32 @@ impl Community {
         let hierarchy_hash = get_content_hash(hierarchies.clone(), None, false).expect("content not found");
         let hierarchy_hash = Base58CryptoHash::try_from(hierarchy_hash).unwrap();
 
+        let config = report::get_report_config();
         let mut report_accounts = self.reports.get(&hierarchy_hash).unwrap_or(HashSet::new());
-        assert!(report_accounts.len() < 5, "can not report");
+        assert!(report_accounts.len() < config.max_reporters as usize, "can not report");
         assert!(!report_accounts.contains(&sender_id), "already report");
         report_accounts.insert(sender_id.clone());
         self.reports.insert(&hierarchy_hash, &report_accounts);
+        report::add_report_reason(&hierarchy_hash, &hierarchies, &sender_id, reason.clone());
+        report::set_queue_entry(&hierarchy_hash, &report_accounts);
+        Event::log_report_content(
+            hierarchies.clone(),
+            sender_id.clone(),
+            reason,
+            Some(json!({
+                "report_count": report_accounts.len()
+            }).to_string())
+        );
+        let hierarchy_hash = String::from(&hierarchy_hash);
+        if matches!(config.hide_threshold, Some(threshold) if report_accounts.len() >= threshold as usize) && !report::is_hidden(&hierarchy_hash) {
+            report::set_hidden(&hierarchy_hash);
+            Event::log_hide_content(
+                hierarchies,
+                Some(json!({
+                    "report_count": report_accounts.len()
+                }).to_string())
+            );
+        }
         set_storage_usage(initial_storage_usage, Some(sender_id));
     }
 
@@ -40,7 +106,11 @@ impl Community {
                 accounts.remove(&sender_id);
                 if accounts.is_empty() {
                     self.reports.remove(&hierarchy_hash);
+                } else {
+                    self.reports.insert(&hierarchy_hash, &accounts);
                 }
+                report::remove_report_reason(&hierarchy_hash, &sender_id);
+                report::set_queue_entry(&hierarchy_hash, &accounts);
             }
         }
         
@@ -52,7 +122,7 @@ impl Community {
         for instruction in instructions {
             match instruction {
                 Instruction::Write(map) => {
-                    account.data.insert(get_predecessor_id().to_string(), json!(map).to_string());
+                    account.set_app_data(get_predecessor_id(), map);
                 },
                 Instruction::Drip(drips) => {
                     if get_root_id(env::current_account_id()) == get_root_id(get_predecessor_id()) {
@@ -3,7 +3,7 @@ use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 use std::str::FromStr;
 
-use account::{Account, OldAccess};
+use account::{Account, OldAccess, OldAccount, VAccount};
 use events::Event;
 use internal::Instruction;
 use near_contract_standards::fungible_token::core::ext_ft_core;
@@ -17,15 +17,16 @@ use drip::{Drip, PendingDrip};
 use proposal::{Proposal, FunctionCall, ActionCall};
 use role::{RoleManagement, OldRoleManagement};
 use uint::hex;
-use utils::{refund_extra_storage_deposit, set, remove, set_storage_usage, get_account, set_account, get_account_id, init_callback};
+use utils::{refund_extra_storage_deposit, set, remove, set_storage_usage, get_account, set_account, lookup_account, get_account_id, init_callback};
 use crate::post::Hierarchy;
 use crate::proposal::ProposalInput;
 use crate::role::Role;
+use crate::view::AccountOutput;
 use crate::utils::{get_arg, get_access_limit, verify, from_rpc_sig, get_predecessor_id};
 use std::convert::TryFrom;
 use role::Permission;
 use account::AssetKey;
-use account::{Access, Relationship};
+use account::{Access, Relationship, ext_nft_core};
 
 
 pub mod utils;
@@ -40,6 +41,12 @@ pub mod resolver;
 pub mod internal;
 pub mod metadata;
 pub mod proposal;
+pub mod mmr;
+pub mod pin;
+pub mod reaction;
+pub mod follow;
+pub mod report;
+pub mod appeal;
 
 
 const JOIN_DEPOSIT: u128 = 50000000000000000000000;
@@ -49,7 +56,7 @@ const JOIN_DEPOSIT: u128 = 50000000000000000000000;
 pub struct Community {
     owner_id: AccountId,
     args: HashMap<String, String>,
-    accounts: LookupMap<AccountId, Account>,
+    accounts: LookupMap<AccountId, OldAccount>,    //legacy accounts, see `utils::get_account`
     reports: UnorderedMap<Base58CryptoHash, HashSet<AccountId>>,
     drip: Drip,
     role_management: RoleManagement,
@@ -61,7 +68,7 @@ pub struct Community {
 pub struct OldCommunity {
     owner_id: AccountId,
     args: HashMap<String, String>,
-    accounts: LookupMap<AccountId, Account>,
+    accounts: LookupMap<AccountId, OldAccount>,
     reports: UnorderedMap<Base58CryptoHash, HashSet<AccountId>>,
     drip: Drip,
     role_management: OldRoleManagement,
@@ -70,6 +77,7 @@ pub struct OldCommunity {
 }
 
 
+/// Default hierarchy depth, also the depth the legacy drip keys and default permissions were laid out for.
 const MAX_LEVEL: usize = 3;
 
 #[derive(BorshSerialize, BorshStorageKey)]
@@ -77,7 +85,27 @@ pub enum StorageKey {
     Report,
     Account,
     Roles,
-    Proposals
+    Proposals,
+    VAccount,
+    DripTable,
+    DecayConfig,
+    RoleDeny,
+    ContentRevision,
+    MaxDepth,
+    MmrNodes,
+    MmrSize,
+    PinnedContent,
+    ReactionKinds,
+    ReactionCounts,
+    LikeCounts,
+    FollowCounts,
+    ReportDetails,
+    ReportConfig,
+    Removals,
+    ReportQueue,
+    ReportQueueTree,
+    ReportQueueKeys,
+    RemovedRoles
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -121,11 +149,11 @@ impl Community {
         account.set_registered(true);
         account.set_permanent(true);
         account.increase_balance(AssetKey::FT(AccountId::from_str("near").unwrap()), JOIN_DEPOSIT);
-        this.accounts.insert(&owner_id, &account);
+        set_account(&owner_id, &account);
         let mut account = Account::new(&env::current_account_id());
         account.set_registered(true);
         account.set_permanent(true);
-        this.accounts.insert(&env::current_account_id(), &account);
+        set_account(&env::current_account_id(), &account);
         this
     }
 
@@ -135,29 +163,7 @@ impl Community {
         let old_this: OldCommunity = env::state_read().expect("ERR_NOT_INITIALIZED");
         assert!(get_predecessor_id() == old_this.owner_id || get_predecessor_id() == env::current_account_id(), "owner only");
         
-        let mut global_permissions = HashMap::new();
-        global_permissions.insert(Permission::AddContent(0), (Relationship::Or, None));
-        global_permissions.insert(Permission::AddContent(1), (Relationship::Or, None));
-        global_permissions.insert(Permission::AddContent(2), (Relationship::Or, None));
-        global_permissions.insert(Permission::DelContent, (Relationship::Or, None));
-        global_permissions.insert(Permission::AddEncryptContent(0), (Relationship::Or, None));
-        global_permissions.insert(Permission::AddEncryptContent(1), (Relationship::Or, None));
-        global_permissions.insert(Permission::AddEncryptContent(2), (Relationship::Or, None));
-        global_permissions.insert(Permission::DelEncryptContent, (Relationship::Or, None));
-        global_permissions.insert(Permission::Like, (Relationship::Or, None));
-        global_permissions.insert(Permission::Unlike, (Relationship::Or, None));
-        global_permissions.insert(Permission::Report, (Relationship::Or, None));
-        global_permissions.insert(Permission::Vote, (Relationship::Or, None));
-        global_permissions.insert(Permission::AddProposal(false), (Relationship::Or, None));
-
-        global_permissions.insert(Permission::AddProposal(true), (Relationship::And, None));
-        global_permissions.insert(Permission::ReportConfirm, (Relationship::And, None));
-        global_permissions.insert(Permission::DelOthersContent, (Relationship::And, None));
-        global_permissions.insert(Permission::SetRole(None), (Relationship::And, None));
-        global_permissions.insert(Permission::DelRole(None), (Relationship::And, None));
-        global_permissions.insert(Permission::AddMember(None), (Relationship::And, None));
-        global_permissions.insert(Permission::RemoveMember(None), (Relationship::And, None));
-        global_permissions.insert(Permission::Other(None), (Relationship::And, None));
+        let global_permissions = role::get_default_global_permissions();
 
         let this = Community {
             owner_id: old_this.owner_id,
@@ -180,21 +186,6 @@ impl Community {
         this
     }
 
-    pub fn follow(&mut self, account_id: AccountId) {
-        init_callback();
-        let sender_id = get_predecessor_id();
-        let hash = env::sha256(&(sender_id.to_string() + "follwing" + &account_id.to_string()).into_bytes());
-        set(&hash, 0);
-        Event::log_follow(sender_id, account_id,None);
-    }
-
-    pub fn unfollow(&mut self, account_id: AccountId) {
-        let sender_id = get_predecessor_id();
-        let hash = env::sha256(&(sender_id.to_string() + "follwing" + &account_id.to_string()).into_bytes());
-        remove(&hash);
-        Event::log_unfollow(sender_id, account_id, None);
-    }
-
     pub fn agree_rules(&mut self) {
         init_callback()
     }
@@ -208,7 +199,7 @@ impl Community {
         let initial_storage_usage = env::storage_usage();
         let sender_id = account_id.unwrap_or(get_predecessor_id());
         
-        let mut account = match self.accounts.get(&sender_id) {
+        let mut account = match lookup_account(&sender_id) {
             Some(account)=> {
                 account
             },
@@ -244,7 +235,7 @@ impl Community {
                 }
             }
         }
-        self.accounts.insert(&sender_id, &account);
+        set_account(&sender_id, &account);
         set_storage_usage(initial_storage_usage, None);
     }
 
@@ -258,7 +249,7 @@ impl Community {
         let account = get_account(&sender_id).get_registered();
         if let Some(mut account) = account {
             account.set_registered(false);
-            self.accounts.insert(&sender_id, &account);
+            set_account(&sender_id, &account);
         }
     }
 
@@ -268,7 +259,7 @@ impl Community {
         let sender_id = get_predecessor_id();
         let mut account = get_account(&sender_id).registered();
         account.increase_balance(AssetKey::FT(AccountId::from_str("near").unwrap()), env::attached_deposit());
-        self.accounts.insert(&sender_id, &account);
+        set_account(&sender_id, &account);
         set_storage_usage(initial_storage_usage, None);
     }
 
@@ -288,7 +279,12 @@ impl Community {
                     ext_ft_core::ext(token_id.clone()).with_attached_deposit(1).ft_transfer(sender_id.clone(), amount, None).into()
                 }
             },
-            AssetKey::NFT(_, _) => PromiseOrValue::Value(()),
+            AssetKey::NFT(contract_id, token_id) => {
+                let token_id = token_id.clone().expect("token id required");
+                account.decrease_nft(contract_id.clone(), token_id.clone());
+                set_account(&sender_id, &account);
+                ext_nft_core::ext(contract_id.clone()).with_attached_deposit(1).nft_transfer(sender_id.clone(), token_id, None, None).into()
+            },
             AssetKey::Drip(_) => PromiseOrValue::Value(())
         };
         set_storage_usage(initial_storage_usage, None);
@@ -356,9 +352,9 @@ impl Community {
         final_args.insert("args".to_string(), args);
         final_args.insert("sender_id".to_string(), sender_id.to_string());
         let mut accounts = HashMap::new();
-        accounts.insert(sender_id.to_string(), json!(get_account(&sender_id).data).to_string());
+        accounts.insert(sender_id.to_string(), json!(AccountOutput::from(get_account(&sender_id))).to_string());
         for account_id in read_accounts {
-            accounts.insert(account_id.to_string(), json!(get_account(&account_id).data).to_string());
+            accounts.insert(account_id.to_string(), json!(AccountOutput::from(get_account(&account_id))).to_string());
         }
         final_args.insert("accounts".to_string(), json!(accounts).to_string());
         Promise::new(to).function_call(method_name, json!(final_args).to_string().into_bytes(), env::attached_deposit(), env::prepaid_gas() / 3).then(
@@ -436,6 +432,53 @@ impl Community {
 }
 
 
+/// Contract setup for tests calling entrypoints, storage carries over between `set_context` calls.
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use std::{collections::HashMap, str::FromStr};
+
+    use near_sdk::{testing_env, test_utils::VMContextBuilder, AccountId, env};
+
+    use crate::{Community, AccessLimit};
+
+    pub fn account(account_id: &str) -> AccountId {
+        AccountId::from_str(account_id).unwrap()
+    }
+
+    fn build_context(predecessor_id: &AccountId, deposit: u128, timestamp: u64) {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(account("community.testnet"))
+            .signer_account_id(predecessor_id.clone())
+            .predecessor_account_id(predecessor_id.clone())
+            .attached_deposit(deposit)
+            .block_timestamp(timestamp)
+            .build());
+    }
+
+    pub fn set_context(predecessor_id: &AccountId) {
+        set_deposit_context(predecessor_id, 0);
+    }
+
+    /// Block time carries over from the previous context.
+    pub fn set_deposit_context(predecessor_id: &AccountId, deposit: u128) {
+        build_context(predecessor_id, deposit, env::block_timestamp());
+    }
+
+    /// Moves block time for the current caller.
+    pub fn set_block_timestamp(timestamp: u64) {
+        build_context(&env::predecessor_account_id(), 0, timestamp);
+    }
+
+    /// Community owned by "owner.testnet" open to everyone, so storage isn't charged.
+    pub fn setup_community(args: HashMap<String, String>) -> Community {
+        set_context(&account("owner.testnet"));
+        let mut community = Community::new(account("owner.testnet"), args);
+        community.access = AccessLimit::Free;
+        env::state_write(&community);
+        community
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{collections::HashMap, str::FromStr};
@@ -0,0 +1,148 @@
+//! Append-only Merkle Mountain Range over the hierarchy hash of every content added to this community.
+//! Nodes are addressed by (height, index within height) and never change once written, so proofs
+//! against any earlier leaf count can still be served. Deleting content does not remove its leaf.
+
+use crate::*;
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[derive(Debug, Clone)]
+pub struct ContentRoot {
+    pub root: Base58CryptoHash,
+    pub leaf_count: U64
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[derive(Debug, Clone)]
+pub struct ContentProof {
+    pub leaf_index: U64,
+    pub leaf_count: U64,
+    pub siblings: Vec<Base58CryptoHash>,
+    pub peaks: Vec<Base58CryptoHash>
+}
+
+fn get_nodes() -> LookupMap<(u8, u64), CryptoHash> {
+    LookupMap::new(StorageKey::MmrNodes)
+}
+
+pub fn get_leaf_count() -> u64 {
+    let size: LazyOption<u64> = LazyOption::new(StorageKey::MmrSize, None);
+    size.get().unwrap_or(0)
+}
+
+fn hash_leaf(hash: &[u8]) -> CryptoHash {
+    env::sha256(&[&[0u8][..], hash].concat())[..].try_into().unwrap()
+}
+
+fn hash_node(left: &CryptoHash, right: &CryptoHash) -> CryptoHash {
+    env::sha256(&[&[1u8][..], &left[..], &right[..]].concat())[..].try_into().unwrap()
+}
+
+/// (height, first leaf) of every mountain for `leaf_count` leaves, highest first.
+fn get_mountains(leaf_count: u64) -> Vec<(u8, u64)> {
+    let mut mountains = Vec::new();
+    let mut start = 0;
+    for height in (0..64).rev() {
+        if leaf_count >> height & 1 == 1 {
+            mountains.push((height as u8, start));
+            start += 1 << height;
+        }
+    }
+    mountains
+}
+
+fn get_peaks(leaf_count: u64) -> Vec<CryptoHash> {
+    let nodes = get_nodes();
+    get_mountains(leaf_count).into_iter().map(|(height, start)| nodes.get(&(height, start >> height)).unwrap()).collect()
+}
+
+fn bag_peaks(peaks: &[CryptoHash]) -> CryptoHash {
+    let mut iter = peaks.iter().rev();
+    let mut root = match iter.next() {
+        Some(v) => *v,
+        None => return [0u8; 32]
+    };
+    for peak in iter {
+        root = hash_node(peak, &root);
+    }
+    root
+}
+
+/// Appends the full hierarchy hash of new content, returns its leaf index.
+pub(crate) fn append(hierarchy_hash: &[u8]) -> u64 {
+    let leaf_index = get_leaf_count();
+    let mut nodes = get_nodes();
+    let mut node = hash_leaf(hierarchy_hash);
+    nodes.insert(&(0, leaf_index), &node);
+    let mut height = 0;
+    let mut index = leaf_index;
+    while index & 1 == 1 {
+        let left = nodes.get(&(height, index - 1)).unwrap();
+        node = hash_node(&left, &node);
+        height += 1;
+        index >>= 1;
+        nodes.insert(&(height, index), &node);
+    }
+    let mut size: LazyOption<u64> = LazyOption::new(StorageKey::MmrSize, None);
+    size.set(&(leaf_index + 1));
+    leaf_index
+}
+
+pub fn get_root(leaf_count: Option<u64>) -> ContentRoot {
+    let leaf_count = leaf_count.unwrap_or(get_leaf_count());
+    assert!(leaf_count <= get_leaf_count(), "leaf count out of range");
+    ContentRoot {
+        root: bag_peaks(&get_peaks(leaf_count)).into(),
+        leaf_count: leaf_count.into()
+    }
+}
+
+pub fn get_proof(leaf_index: u64, leaf_count: Option<u64>) -> ContentProof {
+    let leaf_count = leaf_count.unwrap_or(get_leaf_count());
+    assert!(leaf_index < leaf_count && leaf_count <= get_leaf_count(), "leaf out of range");
+    let nodes = get_nodes();
+    let (height, _) = get_mountains(leaf_count).into_iter().find(|(height, start)| leaf_index < start + (1 << height)).unwrap();
+    let siblings = (0..height).map(|level| nodes.get(&(level, (leaf_index >> level) ^ 1)).unwrap().into()).collect();
+    ContentProof {
+        leaf_index: leaf_index.into(),
+        leaf_count: leaf_count.into(),
+        siblings,
+        peaks: get_peaks(leaf_count).into_iter().map(|v| v.into()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use near_sdk::{AccountId, CryptoHash, serde_json::json};
+    use community_verifier::{Hierarchy, ContentProof};
+
+    use crate::utils::set_content;
+    use super::{get_root, get_proof};
+
+    #[test]
+    pub fn test_content_proof() {
+        let account_id = AccountId::from_str("alice.testnet").unwrap();
+        let mut contents = vec![];
+        for i in 0..7 {
+            let (target_hash, _, leaf_index) = set_content(format!("post {}", i), account_id.clone(), "".to_string(), None, None);
+            assert_eq!(leaf_index, i);
+            contents.push(vec![Hierarchy {
+                target_hash: String::from(&target_hash),
+                account_id: account_id.to_string(),
+                options: None
+            }]);
+        }
+        for leaf_count in 1..=7 {
+            let root: CryptoHash = get_root(Some(leaf_count)).root.into();
+            for leaf_index in 0..leaf_count {
+                let proof = ContentProof::from_json(&json!(get_proof(leaf_index, Some(leaf_count))).to_string()).unwrap();
+                assert!(community_verifier::verify(&contents[leaf_index as usize], &proof, &root));
+                let other = &contents[((leaf_index + 1) % 7) as usize];
+                assert!(!community_verifier::verify(other, &proof, &root));
+            }
+        }
+    }
+}
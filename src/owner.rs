@@ -1,5 +1,7 @@
 use crate::{*, utils::{get_root_id}};
 use utils::get_parent_contract_id;
+use drip::DecayConfig;
+use report::ReportConfig;
 
 #[near_bindgen]
 impl Community {
@@ -55,26 +57,164 @@ impl Community {
         self.access = access;
     }
 
+    /// Owner or a passed proposal (called by the contract itself) can change drip rewards.
+    #[payable]
+    pub fn set_drip_table(&mut self, values: HashMap<String, U128>) {
+        assert_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+        let sender = get_predecessor_id();
+        assert!(sender == self.owner_id || 
+            get_parent_contract_id(env::current_account_id()) == get_predecessor_id() ||
+            env::current_account_id() == get_predecessor_id()
+        , "owner only");
+        drip::set_drip_table(values.clone());
+        set_storage_usage(initial_storage_usage, None);
+        Event::log_other(
+            Some(json!({
+                "drip_table": values
+            }).to_string())
+        );
+    }
+
+    #[payable]
+    pub fn set_decay_config(&mut self, config: DecayConfig) {
+        assert_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+        let sender = get_predecessor_id();
+        assert!(sender == self.owner_id || 
+            get_parent_contract_id(env::current_account_id()) == get_predecessor_id() ||
+            env::current_account_id() == get_predecessor_id()
+        , "owner only");
+        drip::set_decay_config(&config);
+        set_storage_usage(initial_storage_usage, None);
+        Event::log_other(
+            Some(json!({
+                "decay_config": config
+            }).to_string())
+        );
+    }
+
+    #[payable]
+    pub fn set_report_config(&mut self, config: ReportConfig) {
+        assert_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+        let sender = get_predecessor_id();
+        assert!(sender == self.owner_id || 
+            get_parent_contract_id(env::current_account_id()) == get_predecessor_id() ||
+            env::current_account_id() == get_predecessor_id()
+        , "owner only");
+        report::set_report_config(&config);
+        set_storage_usage(initial_storage_usage, None);
+        Event::log_other(
+            Some(json!({
+                "report_config": config
+            }).to_string())
+        );
+    }
+
+    #[payable]
+    pub fn set_reaction_kinds(&mut self, kinds: Vec<String>) {
+        assert_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+        let sender = get_predecessor_id();
+        assert!(sender == self.owner_id || 
+            get_parent_contract_id(env::current_account_id()) == get_predecessor_id() ||
+            env::current_account_id() == get_predecessor_id()
+        , "owner only");
+        reaction::set_reaction_kinds(kinds.clone().into_iter().collect());
+        set_storage_usage(initial_storage_usage, None);
+        Event::log_other(
+            Some(json!({
+                "reaction_kinds": kinds
+            }).to_string())
+        );
+    }
+
+    /// Communities deployed before a permission existed have no global entry for it, so only the owner holds it.
+    /// Adds the default global entry of every permission missing one, entries already set are kept,
+    /// and gives the "mod" role the permissions mods were given after it was seeded.
+    #[payable]
+    pub fn add_missing_permissions(&mut self) {
+        assert_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+        let sender = get_predecessor_id();
+        assert!(sender == self.owner_id || 
+            get_parent_contract_id(env::current_account_id()) == get_predecessor_id() ||
+            env::current_account_id() == get_predecessor_id()
+        , "owner only");
+        let mut added = vec![];
+        for (permission, options) in role::get_default_global_permissions() {
+            if !self.role_management.global_role.contains_key(&permission) {
+                self.role_management.global_role.insert(permission.clone(), options);
+                added.push(permission);
+            }
+        }
+        let mut mod_added = vec![];
+        if let Some(role) = self.role_management.roles.get_mut("mod") {
+            for permission in role::get_later_mod_permissions() {
+                if role.permissions.insert(permission.clone()) {
+                    mod_added.push(permission);
+                }
+            }
+        }
+        set_storage_usage(initial_storage_usage, None);
+        Event::log_other(
+            Some(json!({
+                "global_permissions_added": added,
+                "mod_permissions_added": mod_added
+            }).to_string())
+        );
+    }
+
+    /// Deepest hierarchy length allowed, 1 keeps the community to top level posts only.
+    #[payable]
+    pub fn set_max_depth(&mut self, depth: u8) {
+        assert_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+        let sender = get_predecessor_id();
+        assert!(sender == self.owner_id || 
+            get_parent_contract_id(env::current_account_id()) == get_predecessor_id() ||
+            env::current_account_id() == get_predecessor_id()
+        , "owner only");
+        assert!(depth > 0, "illegal depth");
+        post::set_max_depth(depth);
+        set_storage_usage(initial_storage_usage, None);
+        Event::log_other(
+            Some(json!({
+                "max_depth": depth
+            }).to_string())
+        );
+    }
+
     #[private]  //TODO: open it?
     pub fn distribute(&mut self, list: Vec<(AccountId, AssetKey, U128)>) {
         let mut community = get_account(&env::current_account_id()).registered();
         for (account_id, asset, amount) in list {
             match asset {
                 AssetKey::Drip(_) => panic!("not allowed"), 
+                AssetKey::NFT(_, None) => panic!("token id required"),
                 _ => {}
             };
             let mut account = match get_account(&account_id).get_registered() {
                 Some(v) => v,
                 None => continue
             };
+            if let AssetKey::NFT(contract_id, Some(token_id)) = &asset {
+                if community.get_balance(&asset) == 1 {
+                    community.decrease_nft(contract_id.clone(), token_id.clone());
+                    account.increase_nft(contract_id.clone(), token_id.clone());
+                    set_account(&account_id, &account);
+                }
+                continue
+            }
             let balance = community.get_balance(&asset);
             if let Some(_) = balance.checked_sub(amount.0) {
                 account.increase_balance(asset.clone(), amount.0);
-                self.accounts.insert(&account_id, &account);
+                set_account(&account_id, &account);
                 community.decrease_balance(asset, amount.0)
             }
         }
-        self.accounts.insert(&env::current_account_id(), &community);
+        set_account(&env::current_account_id(), &community);
     }
 }
 
@@ -0,0 +1,127 @@
+use crate::*;
+use post::Hierarchy;
+use utils::get_content_hash;
+
+/// Upper bound of pinned content kept by a community.
+pub const MAX_PINNED: usize = 20;
+
+#[derive(BorshDeserialize, BorshSerialize)]
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[derive(Debug, Clone)]
+pub struct PinnedContent {
+    pub hierarchies: Vec<Hierarchy>,
+    pub hierarchy_hash: String,
+    pub pinned_by: AccountId,
+    pub timestamp: U64
+}
+
+fn get_pinned_list() -> LazyOption<Vec<PinnedContent>> {
+    LazyOption::new(StorageKey::PinnedContent, None)
+}
+
+/// Pinned content in display order, first is on top.
+pub fn get_pinned() -> Vec<PinnedContent> {
+    get_pinned_list().get().unwrap_or_default()
+}
+
+fn set_pinned(pinned: Vec<PinnedContent>) {
+    let mut list = get_pinned_list();
+    if pinned.is_empty() {
+        list.remove();
+    } else {
+        list.set(&pinned);
+    }
+}
+
+/// Drops the content from the pinned list, returns the removed entry.
+pub(crate) fn unpin(hierarchy_hash: &String) -> Option<PinnedContent> {
+    let mut pinned = get_pinned();
+    let index = pinned.iter().position(|v| v.hierarchy_hash == *hierarchy_hash)?;
+    let removed = pinned.remove(index);
+    set_pinned(pinned);
+    Event::log_unpin_content(removed.hierarchies.clone(), None);
+    Some(removed)
+}
+
+#[near_bindgen]
+impl Community {
+    /// Pins content at `position` (top by default), pinning it again moves it.
+    /// Communities deployed before pinning need `add_missing_permissions` before mods can pin.
+    pub fn pin_content(&mut self, hierarchies: Vec<Hierarchy>, position: Option<u32>) {
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = get_predecessor_id();
+        assert!(self.can_execute_action(None, None, Permission::PinContent), "not allowed");
+        let hierarchy_hash = get_content_hash(hierarchies.clone(), None, false).expect("content not found");
+
+        let mut pinned = get_pinned();
+        pinned.retain(|v| v.hierarchy_hash != hierarchy_hash);
+        assert!(pinned.len() < MAX_PINNED, "too many pinned");
+        let position = std::cmp::min(position.unwrap_or(0) as usize, pinned.len());
+        pinned.insert(position, PinnedContent {
+            hierarchies: hierarchies.clone(),
+            hierarchy_hash,
+            pinned_by: sender_id,
+            timestamp: env::block_timestamp().into()
+        });
+        set_pinned(pinned);
+        Event::log_pin_content(hierarchies, Some(json!({ "position": position }).to_string()));
+        set_storage_usage(initial_storage_usage, None);
+    }
+
+    pub fn unpin_content(&mut self, hierarchies: Vec<Hierarchy>) {
+        let initial_storage_usage = env::storage_usage();
+        assert!(self.can_execute_action(None, None, Permission::PinContent), "not allowed");
+        let hierarchy_hash = get_content_hash(hierarchies, None, true).unwrap();
+        unpin(&hierarchy_hash).expect("not pinned");
+        set_storage_usage(initial_storage_usage, None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, str::FromStr};
+
+    use near_sdk::AccountId;
+
+    use crate::post::Hierarchy;
+    use crate::role::Permission;
+    use crate::test_utils::{account, set_context, set_deposit_context, setup_community};
+    use super::{PinnedContent, get_pinned, set_pinned, unpin};
+
+    #[test]
+    pub fn test_add_missing_pin_permission() {
+        let mut community = setup_community(HashMap::new());
+        // state of a community deployed before pinning
+        community.role_management.global_role.remove(&Permission::PinContent);
+        community.role_management.roles.get_mut("mod").unwrap().permissions.remove(&Permission::PinContent);
+        let moderator = account("mod.testnet");
+        community.role_management.roles.get("mod").unwrap().add_member(&moderator, &HashMap::new());
+        set_context(&account("alice.testnet"));
+        let target_hash = community.add_content("post".to_string(), vec![], None);
+        let hierarchies = vec![Hierarchy { target_hash, account_id: account("alice.testnet"), options: None }];
+
+        set_deposit_context(&account("owner.testnet"), 1);
+        community.add_missing_permissions();
+        assert!(community.role_management.global_role.contains_key(&Permission::PinContent));
+        set_context(&moderator);
+        community.pin_content(hierarchies, None);
+        assert_eq!(get_pinned().len(), 1);
+    }
+
+    #[test]
+    pub fn test_unpin() {
+        let account_id = AccountId::from_str("mod.testnet").unwrap();
+        let pinned = ["a", "b", "c"].iter().map(|hash| PinnedContent {
+            hierarchies: vec![],
+            hierarchy_hash: hash.to_string(),
+            pinned_by: account_id.clone(),
+            timestamp: 0.into()
+        }).collect();
+        set_pinned(pinned);
+        assert!(unpin(&"b".to_string()).is_some());
+        assert!(unpin(&"b".to_string()).is_none());
+        let hashes: Vec<String> = get_pinned().into_iter().map(|v| v.hierarchy_hash).collect();
+        assert_eq!(hashes, vec!["a".to_string(), "c".to_string()]);
+    }
+}
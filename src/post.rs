@@ -3,7 +3,7 @@ use std::{convert::TryInto, str::FromStr};
 use near_sdk::CryptoHash;
 
 use crate::{*, utils::{get, check_and_set, check, init_callback}, drip::get_map_value};    
-use utils::{get_content_hash, set_content};
+use utils::{get_content_hash, get_stored_content_hash, set_content};
 
 // #[derive(Serialize, Deserialize)]
 // #[serde(crate = "near_sdk::serde")]
@@ -41,6 +41,176 @@ pub struct InputArgs {
     options: Option<HashMap<String, String>>
 }
 
+/// Head of a content's edit chain. The chain starts at the original `target_hash` and every edit
+/// moves it to `sha256(prev_revision + sha256(args + timestamp))`, so replaying `ContentEdit`
+/// events from the original post must end at `head`.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[derive(Debug, Clone)]
+pub struct ContentRevision {
+    pub head: Base58CryptoHash,
+    pub count: U64
+}
+
+pub(crate) fn get_revision_hash(prev_revision: &Base58CryptoHash, args: &str, timestamp: u64) -> Base58CryptoHash {
+    let content_hash: CryptoHash = env::sha256(&(args.to_string() + &timestamp.to_string()).into_bytes())[..].try_into().unwrap();
+    let revision_str = String::from(prev_revision) + &String::from(&Base58CryptoHash::from(content_hash));
+    let revision_hash: CryptoHash = env::sha256(&revision_str.into_bytes())[..].try_into().unwrap();
+    Base58CryptoHash::from(revision_hash)
+}
+
+/// Option keys scheduling content, nanosecond timestamps. Before `publish_at` and from `expire_at` on
+/// the content can't be found through `get_content_hash`, so it can't be replied to, liked or shared.
+pub const PUBLISH_AT: &str = "publish_at";
+pub const EXPIRE_AT: &str = "expire_at";
+
+fn get_schedule(options: &Option<HashMap<String, String>>, key: &str) -> Option<u64> {
+    options.as_ref()
+        .and_then(|options| options.get(key))
+        .map(|v| v.parse::<u64>().expect("invalid schedule"))
+}
+
+pub(crate) fn is_live(options: &Option<HashMap<String, String>>) -> bool {
+    let now = env::block_timestamp();
+    get_schedule(options, PUBLISH_AT).map_or(true, |publish_at| publish_at <= now) &&
+        get_schedule(options, EXPIRE_AT).map_or(true, |expire_at| expire_at > now)
+}
+
+pub(crate) fn is_expired(options: &Option<HashMap<String, String>>) -> bool {
+    get_schedule(options, EXPIRE_AT).map_or(false, |expire_at| expire_at <= env::block_timestamp())
+}
+
+pub(crate) fn check_schedule(options: &Option<HashMap<String, String>>) {
+    let publish_at = get_schedule(options, PUBLISH_AT).unwrap_or(0);
+    if let Some(expire_at) = get_schedule(options, EXPIRE_AT) {
+        assert!(expire_at > env::block_timestamp() && expire_at > publish_at, "invalid schedule");
+    }
+}
+
+/// Option key of encrypted content holding the JSON `Access` needed to decrypt it.
+pub const ENCRYPT_ACCESS: &str = "access";
+
+pub(crate) fn get_encrypt_access(options: &Option<HashMap<String, String>>) -> Option<Access> {
+    options.as_ref()
+        .and_then(|options| options.get(ENCRYPT_ACCESS))
+        .map(|access| serde_json::from_str::<Access>(access).expect("invalid access"))
+}
+
+fn get_receipt_key(account_id: &AccountId, hierarchy_hash: &str) -> Vec<u8> {
+    env::sha256(&(account_id.to_string() + "decrypt" + hierarchy_hash).into_bytes())
+}
+
+/// Paid decryption of content by the account, `Some(u64::MAX)` never expires. Expired receipts read as `None`.
+pub fn get_decrypt_receipt(account_id: &AccountId, hierarchy_hash: &str) -> Option<u64> {
+    match get::<u64>(&get_receipt_key(account_id, hierarchy_hash)) {
+        Some(until) if until > env::block_timestamp() => Some(until),
+        _ => None
+    }
+}
+
+pub(crate) fn set_decrypt_receipt(account_id: &AccountId, hierarchy_hash: &str, access: &Access) -> u64 {
+    let until = match access.expire_duration {
+        Some(duration) => env::block_timestamp().saturating_add(duration.0),
+        None => u64::MAX
+    };
+    set(&get_receipt_key(account_id, hierarchy_hash), until);
+    until
+}
+
+/// Maximum hierarchy length of content in this community, a post alone is depth 1.
+pub fn get_max_depth() -> usize {
+    let depth: LazyOption<u8> = LazyOption::new(StorageKey::MaxDepth, None);
+    depth.get().map(|v| v as usize).unwrap_or(MAX_LEVEL)
+}
+
+pub(crate) fn set_max_depth(depth: u8) {
+    let mut stored: LazyOption<u8> = LazyOption::new(StorageKey::MaxDepth, None);
+    stored.set(&depth);
+}
+
+fn get_revisions() -> LookupMap<String, ContentRevision> {
+    LookupMap::new(StorageKey::ContentRevision)
+}
+
+pub(crate) fn get_content_revision(hierarchy_hash: &String) -> Option<ContentRevision> {
+    get_revisions().get(hierarchy_hash)
+}
+
+fn get_like_counts() -> LookupMap<String, u64> {
+    LookupMap::new(StorageKey::LikeCounts)
+}
+
+pub fn get_like_count(hierarchy_hash: &String) -> u64 {
+    get_like_counts().get(hierarchy_hash).unwrap_or(0)
+}
+
+fn update_like_count(hierarchy_hash: &String, increase: bool) {
+    let count = get_like_count(hierarchy_hash);
+    let count = if increase { count + 1 } else { count.saturating_sub(1) };
+    if count == 0 {
+        get_like_counts().remove(hierarchy_hash);
+    } else {
+        get_like_counts().insert(hierarchy_hash, &count);
+    }
+}
+
+/// Set on the first like, an account is paid for liking a content and the author for being liked by it only once.
+fn get_liked_key(account_id: &AccountId, hierarchy_hash: &str) -> Vec<u8> {
+    env::sha256(&(account_id.to_string() + "liked" + hierarchy_hash).into_bytes())
+}
+
+fn get_shared_key(account_id: &AccountId, hierarchy_hash: &str) -> Vec<u8> {
+    env::sha256(&(account_id.to_string() + "share" + hierarchy_hash).into_bytes())
+}
+
+/// `be_liked` items (author and royalties) paid for this like, taken back on unlike.
+/// Likes recorded before the items were kept hold an i32 0 and read as nothing paid.
+fn get_like_drips(hash: &[u8]) -> Vec<(AccountId, String, U128)> {
+    match env::storage_read(&hash[0..16]) {
+        Some(v) => Vec::try_from_slice(&v).unwrap_or_default(),
+        None => Vec::new()
+    }
+}
+
+/// Per content state `remove_content_state` drops and an upheld appeal puts back, pins are left to moderators.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[derive(Debug, Clone)]
+pub struct ContentState {
+    pub revision: Option<ContentRevision>,
+    pub like_count: U64,
+    pub reactions: HashMap<String, U64>
+}
+
+pub(crate) fn get_content_state(hierarchy_hash: &String) -> ContentState {
+    ContentState {
+        revision: get_content_revision(hierarchy_hash),
+        like_count: get_like_count(hierarchy_hash).into(),
+        reactions: reaction::get_reaction_counts(hierarchy_hash).into_iter().map(|(k, v)| (k, v.into())).collect()
+    }
+}
+
+pub(crate) fn restore_content_state(hierarchy_hash: &String, state: &ContentState) {
+    if let Some(revision) = &state.revision {
+        get_revisions().insert(hierarchy_hash, revision);
+    }
+    if state.like_count.0 > 0 {
+        get_like_counts().insert(hierarchy_hash, &state.like_count.0);
+    }
+    reaction::set_reaction_counts(hierarchy_hash, state.reactions.iter().map(|(k, v)| (k.clone(), v.0)).collect());
+}
+
+/// Clears everything kept alongside the content marker when content is removed.
+pub(crate) fn remove_content_state(hierarchy_hash: &String) {
+    get_revisions().remove(hierarchy_hash);
+    pin::unpin(hierarchy_hash);
+    reaction::remove_reaction_counts(hierarchy_hash);
+    get_like_counts().remove(hierarchy_hash);
+    report::unhide(hierarchy_hash);
+}
+
 #[derive(BorshDeserialize, BorshSerialize)]
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
@@ -56,62 +226,78 @@ pub enum Report {
 impl Community {
 
     pub fn add_content(&mut self, args: String, hierarchies: Vec<Hierarchy>, options: Option<HashMap<String, String>>) -> Base58CryptoHash {
-        // TODO: avoid hash collision through a loop
+        assert!(get_encrypt_access(&options).is_none(), "encrypted content not allowed");
+        let permission = Permission::AddContent(hierarchies.len() as u8);
+        self.internal_add_content(args, hierarchies, options, permission)
+    }
+
+    /// Adds content readable only to those meeting the `Access` in `options["access"]`,
+    /// paid access is bought by burning drip through `ft_on_burn` with `MsgInput::Decrypt`.
+    pub fn add_encrypt_content(&mut self, args: String, hierarchies: Vec<Hierarchy>, options: HashMap<String, String>) -> Base58CryptoHash {
+        let options = Some(options);
+        let access = get_encrypt_access(&options).expect("access required");
+        if access.is_payment {
+            let drip_contract = get_arg::<AccountId>(DRIP_CONTRACT).expect("drip contract not set");
+            let amount = resolver::get_burn_amount(&access.condition, &drip_contract, &env::current_account_id());
+            assert!(amount.unwrap_or(0) > 0, "paid access must be bought by burning drip");
+        }
+        let permission = Permission::AddEncryptContent(hierarchies.len() as u8);
+        self.internal_add_content(args, hierarchies, options, permission)
+    }
+
+    pub fn like(&mut self, hierarchies: Vec<Hierarchy>) {
         init_callback();
         let initial_storage_usage = env::storage_usage();
         let sender_id = get_predecessor_id();
-
-        assert!(self.can_execute_action(None, None, Permission::AddContent(hierarchies.len() as u8)), "not allowed");
-
-        assert!(hierarchies.len() < MAX_LEVEL, "error");
-
-        let hash_prefix = get_content_hash(hierarchies.clone(), None, false).expect("content not found");
-        let target_hash = set_content(args.clone(), sender_id.clone(), hash_prefix.clone(), options.clone(), None);
-
-        let mut prev_content_count = None;
-        if hierarchies.len() > 0 {
-            let prev_hash = CryptoHash::from(Base58CryptoHash::try_from(hash_prefix.clone()).unwrap()).to_vec();
-            let mut val: u8 = get(&prev_hash).unwrap();
-            prev_content_count = Some(val.clone());
-            val += 1;
-            if val > 1 {
-                val = 1;
+        assert!(self.can_execute_action(None, None, Permission::Like), "not allowed");
+        follow::assert_not_blocked(&hierarchies, &sender_id);
+        let hierarchy_hash = get_content_hash(hierarchies.clone(), None, false).expect("content not found");
+        report::assert_not_hidden(&hierarchy_hash);
+        let hash = env::sha256(&(sender_id.to_string() + "like" + &hierarchy_hash.to_string()).into_bytes());
+        let mut drips = Vec::new();
+        if !check(&hash) {
+            let mut be_liked = Vec::new();
+            if !check_and_set(&get_liked_key(&sender_id, &hierarchy_hash), 0) {
+                drips = self.drip.set_like_drip(hierarchies.clone(), sender_id);
+                be_liked = drips.iter().filter(|(_, key, _)| key.starts_with("be_liked")).cloned().collect();
             }
-            set(&prev_hash, val)
+            set(&hash, be_liked);
+            update_like_count(&hierarchy_hash, true);
         }
-
-        let drips = self.drip.set_content_drip(hierarchies.clone(), sender_id.clone(), prev_content_count);
-        Event::log_add_content(
-            args, 
-            [hierarchies, vec![Hierarchy { 
-                target_hash, 
-                account_id: sender_id,
-                options
-            }]].concat(),
+        Event::log_like_content(
+            hierarchies,
             Some(json!({
                 "drips": drips
             }).to_string())
         );
         set_storage_usage(initial_storage_usage, None);
-        target_hash
     }
 
-    pub fn like(&mut self, hierarchies: Vec<Hierarchy>) {
+    /// Reshares content, `through` is the account the sender saw it from.
+    /// Only the first share of a content by an account pays drip, and `through` only gets referral drip if it shared the content itself.
+    pub fn share(&mut self, hierarchies: Vec<Hierarchy>, through: Option<AccountId>, memo: Option<String>) {
         init_callback();
         let initial_storage_usage = env::storage_usage();
         let sender_id = get_predecessor_id();
-        assert!(self.can_execute_action(None, None, Permission::Like), "not allowed");
+        assert!(self.can_execute_action(None, None, Permission::Share), "not allowed");
         let hierarchy_hash = get_content_hash(hierarchies.clone(), None, false).expect("content not found");
-        let hash = env::sha256(&(sender_id.to_string() + "like" + &hierarchy_hash.to_string()).into_bytes());
-        let exist = check_and_set(&hash, 0);
+        report::assert_not_hidden(&hierarchy_hash);
+        let exist = check_and_set(&get_shared_key(&sender_id, &hierarchy_hash), 0);
+        if let Some(through) = through.clone() {
+            let hash = env::sha256(&(sender_id.to_string() + "viewed" + &hierarchy_hash + "through" + through.as_str()).into_bytes());
+            set(&hash, 0);
+        }
         let mut drips = Vec::new();
         if !exist {
-            drips = self.drip.set_like_drip(hierarchies.clone(), sender_id);
+            let referrer = through.clone().filter(|through| check(&get_shared_key(through, &hierarchy_hash)));
+            drips = self.drip.set_share_drip(hierarchies.clone(), sender_id, referrer);
         }
-        Event::log_like_content(
+        Event::log_share_content(
             hierarchies,
+            through,
             Some(json!({
-                "drips": drips
+                "drips": drips,
+                "memo": memo
             }).to_string())
         );
         set_storage_usage(initial_storage_usage, None);
@@ -124,21 +310,61 @@ impl Community {
         let hierarchy_hash = get_content_hash(hierarchies.clone(), None, false).expect("content not found");
 
         let hash = env::sha256(&(sender_id.to_string() + "like" + &hierarchy_hash.to_string()).into_bytes());
-        assert!(check_and_set(&hash, 0), "illegal");
-        Event::log_unlike_content(hierarchies, None);
+        assert!(check(&hash), "illegal");
+        let be_liked = get_like_drips(&hash);
+        remove(&hash);
+        update_like_count(&hierarchy_hash, false);
+        let drips = self.drip.claw_back_drips(&be_liked);
+        Event::log_unlike_content(
+            hierarchies,
+            Some(json!({
+                "drips": drips
+            }).to_string())
+        );
         set_storage_usage(initial_storage_usage, None);
     }
 
+    /// Replaces the content's args in place, the hierarchy hash and therefore all replies stay untouched.
+    pub fn edit_content(&mut self, args: String, hierarchies: Vec<Hierarchy>) -> Base58CryptoHash {
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = get_predecessor_id();
+        assert!(hierarchies.len() > 0, "content not found");
+        let hierarchy = hierarchies.get(hierarchies.len() - 1).unwrap();
+        // encrypted content is edited under the permission it was added with
+        let level = (hierarchies.len() - 1) as u8;
+        let permission = match get_encrypt_access(&hierarchy.options) {
+            Some(_) => Permission::AddEncryptContent(level),
+            None => Permission::AddContent(level)
+        };
+        assert!(self.can_execute_action(None, None, permission), "not allowed");
+        assert!(hierarchy.account_id == sender_id, "not content owner");
+
+        let hierarchy_hash = get_stored_content_hash(hierarchies.clone()).expect("content not found");
+        let (prev_revision, count) = match get_content_revision(&hierarchy_hash) {
+            Some(revision) => (revision.head, revision.count.0),
+            None => (hierarchy.target_hash, 0)
+        };
+        let revision = get_revision_hash(&prev_revision, &args, env::block_timestamp());
+        get_revisions().insert(&hierarchy_hash, &ContentRevision {
+            head: revision,
+            count: (count + 1).into()
+        });
+        Event::log_edit_content(args, hierarchies, prev_revision, revision, None);
+        set_storage_usage(initial_storage_usage, None);
+        revision
+    }
+
     pub fn del_content(&mut self, hierarchies: Vec<Hierarchy>) {
         let initial_storage_usage = env::storage_usage();
         let sender_id = get_predecessor_id();
         assert!(self.can_execute_action(None, None, Permission::DelContent), "not allowed");
         assert!(hierarchies.get(hierarchies.len() - 1).unwrap().account_id == sender_id, "not content owner");
 
-        let hierarchy_hash = match get_content_hash(hierarchies.clone(), None, false) {
+        let hierarchy_hash = match get_stored_content_hash(hierarchies.clone()) {
             Some(v) => v,
             None => return
         };
+        remove_content_state(&hierarchy_hash);
         let hierarchy_hash = Base58CryptoHash::try_from(hierarchy_hash).unwrap();
         let hierarchy_hash = CryptoHash::from(hierarchy_hash).to_vec();
         remove(&hierarchy_hash);
@@ -146,6 +372,53 @@ impl Community {
         set_storage_usage(initial_storage_usage, None);
     }
 
+    pub fn del_encrypt_content(&mut self, hierarchies: Vec<Hierarchy>) {
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = get_predecessor_id();
+        assert!(self.can_execute_action(None, None, Permission::DelEncryptContent), "not allowed");
+        let hierarchy = hierarchies.get(hierarchies.len() - 1).unwrap();
+        assert!(hierarchy.account_id == sender_id, "not content owner");
+        assert!(get_encrypt_access(&hierarchy.options).is_some(), "not encrypted");
+
+        let hierarchy_hash = match get_stored_content_hash(hierarchies.clone()) {
+            Some(v) => v,
+            None => return
+        };
+        remove_content_state(&hierarchy_hash);
+        let hierarchy_hash = Base58CryptoHash::try_from(hierarchy_hash).unwrap();
+        remove(&CryptoHash::from(hierarchy_hash).to_vec());
+        Event::log_del_content(hierarchies, None);
+        set_storage_usage(initial_storage_usage, None);
+    }
+
+    /// Removes expired content, anyone can call it and the freed storage is refunded to each author.
+    pub fn sweep_expired_content(&mut self, contents: Vec<Vec<Hierarchy>>) {
+        for hierarchies in contents {
+            let initial_storage_usage = env::storage_usage();
+            let hierarchy = match hierarchies.last() {
+                Some(v) => v.clone(),
+                None => continue
+            };
+            if !is_expired(&hierarchy.options) {
+                continue
+            }
+            let hierarchy_hash = match get_stored_content_hash(hierarchies.clone()) {
+                Some(v) => v,
+                None => continue
+            };
+            remove_content_state(&hierarchy_hash);
+            let hierarchy_hash = Base58CryptoHash::try_from(hierarchy_hash).unwrap();
+            remove(&CryptoHash::from(hierarchy_hash).to_vec());
+            Event::log_del_content(hierarchies, Some(json!({ "expired": true }).to_string()));
+            // storage of authors who have quit stays with the community
+            let refund_id = match get_account(&hierarchy.account_id).is_registered() {
+                true => hierarchy.account_id,
+                false => env::current_account_id()
+            };
+            set_storage_usage(initial_storage_usage, Some(refund_id));
+        }
+    }
+
     pub fn report_confirm(&mut self, hierarchies: Vec<Hierarchy>, report: Report) {
         let initial_storage_usage = env::storage_usage();
         let sender_id = get_predecessor_id();
@@ -154,20 +427,31 @@ impl Community {
         let hierarchy = hierarchies.get(hierarchies.len() - 1).unwrap();
         assert!(self.get_user_mod_level(&hierarchy.account_id) < self.get_user_mod_level(&sender_id) || sender_id == self.owner_id, "not allowed");
 
-        let hierarchy_hash = get_content_hash(hierarchies.clone(), None, false).expect("content not found");
-        let hierarchy_hash = Base58CryptoHash::try_from(hierarchy_hash).unwrap();
+        let hierarchy_hash_str = get_stored_content_hash(hierarchies.clone()).expect("content not found");
+        let hierarchy_hash = Base58CryptoHash::try_from(hierarchy_hash_str.clone()).unwrap();
         let accounts = self.reports.get(&hierarchy_hash).unwrap_or(HashSet::new());
         self.reports.remove(&hierarchy_hash);
+        report::remove_report_detail(&hierarchy_hash);
+        report::remove_queue_entry(&hierarchy_hash);
+        // approved content is deleted below, which drops the hidden marker with the rest of its state
+        if !matches!(report, Report::Approve) && report::unhide(&hierarchy_hash_str) {
+            Event::log_unhide_content(hierarchies.clone(), None);
+        }
+        Event::log_report_resolved(hierarchies.clone(), report.clone(), accounts.iter().cloned().collect(), None);
         let mut drips = vec![];
         match report {
             Report::Approve => {
-                remove(&CryptoHash::from(hierarchy_hash).to_vec());
+                let mut reporters = vec![];
                 for account_id in &accounts {
                     if *account_id == sender_id {
                         continue
                     }
-                    drips = self.drip.set_report_drip(hierarchies.clone(), account_id.clone());
+                    reporters.push(account_id.clone());
+                    drips.extend(self.drip.set_report_drip(hierarchies.clone(), account_id.clone()));
                 }
+                appeal::record_removal(&hierarchy_hash_str, hierarchies.clone(), sender_id.clone(), self.get_user_mod_level(&sender_id), reporters, drips.clone());
+                remove_content_state(&hierarchy_hash_str);
+                remove(&CryptoHash::from(hierarchy_hash).to_vec());
                 Event::log_del_content(
                     hierarchies,
                     Some(json!({
@@ -202,10 +486,12 @@ impl Community {
 
         let hierarchy = hierarchies.get(hierarchies.len() - 1).unwrap();
         assert!(self.get_user_mod_level(&hierarchy.account_id) < self.get_user_mod_level(&sender_id), "not allowed");
-        let hierarchy_hash = match get_content_hash(hierarchies.clone(), None, false) {
+        let hierarchy_hash = match get_stored_content_hash(hierarchies.clone()) {
             Some(v) => v,
             None => return
         };
+        appeal::record_removal(&hierarchy_hash, hierarchies.clone(), sender_id.clone(), self.get_user_mod_level(&sender_id), vec![], vec![]);
+        remove_content_state(&hierarchy_hash);
         let hierarchy_hash = Base58CryptoHash::try_from(hierarchy_hash).unwrap();
         remove(&CryptoHash::from(hierarchy_hash).to_vec());
         Event::log_del_content(hierarchies, None);
@@ -225,22 +511,23 @@ pub extern "C" fn add_long_content() {
     let options = args.options.clone();
     let sender_id = get_predecessor_id();
     let mut contract: Community = env::state_read().unwrap();
+    assert!(get_encrypt_access(&options).is_none(), "encrypted content not allowed");
+    check_schedule(&options);
     assert!(contract.can_execute_action(None, None, Permission::AddContent(hierarchies.len() as u8)), "not allowed");
 
-    assert!(hierarchies.len() < MAX_LEVEL, "error");
+    assert!(hierarchies.len() < get_max_depth(), "error");
+    follow::assert_not_blocked(&hierarchies, &sender_id);
 
     let hash_prefix = get_content_hash(hierarchies.clone(), None, false).expect("content not found");
-    let target_hash = set_content(json!(args.clone()).to_string(), sender_id.clone(), hash_prefix.clone(), options.clone(), None);
+    report::assert_not_hidden(&hash_prefix);
+    let (target_hash, nonce, mmr_index) = set_content(json!(args.clone()).to_string(), sender_id.clone(), hash_prefix.clone(), options.clone(), None);
 
     let mut prev_content_count = None;
     if hierarchies.len() > 0 {
         let prev_hash = CryptoHash::from(Base58CryptoHash::try_from(hash_prefix.clone()).unwrap()).to_vec();
         let mut val = get::<u8>(&prev_hash).unwrap();
         prev_content_count = Some(val.clone());
-        val += 1;
-        if val > 3 {
-            val = 3;
-        }
+        val = val.saturating_add(1);
         set(&prev_hash, val)
     }
 
@@ -253,7 +540,10 @@ pub extern "C" fn add_long_content() {
             options
         }]].concat(),
         Some(json!({
-            "drips": drips
+            "drips": drips,
+            "nonce": nonce,
+            "mmr_index": U64::from(mmr_index),
+            "mmr_root": mmr::get_root(None).root
         }).to_string())
     );
     set_storage_usage(initial_storage_usage, None);
@@ -267,8 +557,90 @@ mod tests {
 
     use near_sdk::{AccountId, env, json_types::Base58CryptoHash};
 
-    use super::Hierarchy;
+    use std::collections::HashMap;
+
+    use near_sdk::json_types::{U128, U64};
+    use near_sdk::serde_json::json;
+
+    use crate::account::{Access, Condition, DripCondition};
+    use crate::utils::{self, set_content, get_stored_content_hash};
+    use super::{Hierarchy, get_revision_hash, get_content_revision, get_decrypt_receipt, set_decrypt_receipt, get_encrypt_access, ENCRYPT_ACCESS};
+    use super::{is_live, is_expired, PUBLISH_AT, EXPIRE_AT};
+    use super::{get_like_count, update_like_count, get_like_drips};
+    use crate::test_utils::{setup_community, set_context, set_block_timestamp, account};
+
+
+    #[test]
+    pub fn test_like_count() {
+        let hierarchy_hash = "5EVZZTdCcMQ6Di5fq2Zw1HuFd4chQ9KK4DG3byVwiSyp".to_string();
+        update_like_count(&hierarchy_hash, true);
+        update_like_count(&hierarchy_hash, true);
+        update_like_count(&hierarchy_hash, false);
+        assert_eq!(get_like_count(&hierarchy_hash), 1);
+        update_like_count(&hierarchy_hash, false);
+        update_like_count(&hierarchy_hash, false);
+        assert_eq!(get_like_count(&hierarchy_hash), 0);
+
+        let legacy = env::sha256("legacy".as_bytes());
+        utils::set(&legacy, 0);
+        assert!(get_like_drips(&legacy).is_empty());
+    }
 
+    #[test]
+    pub fn test_share_referral() {
+        let mut community = setup_community(HashMap::new());
+        let author = account("alice.testnet");
+        set_context(&author);
+        let target_hash = community.add_content("post".to_string(), vec![], None);
+        let hierarchies = vec![Hierarchy { target_hash, account_id: author, options: None }];
+
+        // carol never shared the content, so sharing through her pays no referral
+        set_context(&account("bob.testnet"));
+        community.share(hierarchies.clone(), Some(account("carol.testnet")), None);
+        assert_eq!(utils::get_account(&account("carol.testnet")).get_drip(), 0);
+
+        let bob_drip = utils::get_account(&account("bob.testnet")).get_drip();
+        set_context(&account("dave.testnet"));
+        community.share(hierarchies, Some(account("bob.testnet")), None);
+        assert!(utils::get_account(&account("bob.testnet")).get_drip() > bob_drip);
+    }
+
+    #[test]
+    pub fn test_relike() {
+        let mut community = setup_community(HashMap::new());
+        let author = account("alice.testnet");
+        let liker = account("bob.testnet");
+        let royalty = account("carol.testnet");
+        let mut options = HashMap::new();
+        options.insert("drip_royalties".to_string(), json!({ "carol.testnet": 10 }).to_string());
+        set_context(&author);
+        let target_hash = community.add_content("post".to_string(), vec![], Some(options.clone()));
+        let hierarchies = vec![Hierarchy { target_hash, account_id: author.clone(), options: Some(options) }];
+        let hierarchy_hash = utils::get_content_hash(hierarchies.clone(), None, false).unwrap();
+        let author_drip = utils::get_account(&author).get_drip();
+
+        set_context(&liker);
+        community.like(hierarchies.clone());
+        let liker_drip = utils::get_account(&liker).get_drip();
+        assert!(utils::get_account(&author).get_drip() > author_drip);
+        assert!(utils::get_account(&royalty).get_drip() > 0);
+        assert!(liker_drip > 0);
+        assert_eq!(get_like_count(&hierarchy_hash), 1);
+
+        community.unlike(hierarchies.clone());
+        assert_eq!(utils::get_account(&author).get_drip(), author_drip);
+        assert_eq!(utils::get_account(&royalty).get_drip(), 0);
+        assert_eq!(get_like_count(&hierarchy_hash), 0);
+
+        // liking again is counted but pays nobody, so like/unlike cycles can't mint drip
+        community.like(hierarchies.clone());
+        community.unlike(hierarchies.clone());
+        community.like(hierarchies);
+        assert_eq!(utils::get_account(&author).get_drip(), author_drip);
+        assert_eq!(utils::get_account(&royalty).get_drip(), 0);
+        assert_eq!(utils::get_account(&liker).get_drip(), liker_drip);
+        assert_eq!(get_like_count(&hierarchy_hash), 1);
+    }
 
     #[test]
     pub fn test() {
@@ -292,6 +664,76 @@ mod tests {
         Some(hash_prefix)
     }
 
+    #[test]
+    pub fn test_decrypt_receipt() {
+        let account_id = AccountId::from_str("reader.testnet").unwrap();
+        let hierarchy_hash = "5EVZZTdCcMQ6Di5fq2Zw1HuFd4chQ9KK4DG3byVwiSyp".to_string();
+        let mut access = Access {
+            condition: Condition::DripCondition(DripCondition {
+                token_id: Some(AccountId::from_str("drip.testnet").unwrap()),
+                contract_id: AccountId::from_str("community.testnet").unwrap(),
+                amount_to_access: U128::from(100)
+            }),
+            expire_duration: None,
+            is_payment: true,
+            options: None
+        };
+        assert!(get_decrypt_receipt(&account_id, &hierarchy_hash).is_none());
+        set_decrypt_receipt(&account_id, &hierarchy_hash, &access);
+        assert_eq!(get_decrypt_receipt(&account_id, &hierarchy_hash), Some(u64::MAX));
+        access.expire_duration = Some(U64::from(0));
+        set_decrypt_receipt(&account_id, &hierarchy_hash, &access);
+        assert!(get_decrypt_receipt(&account_id, &hierarchy_hash).is_none());
+
+        let mut options = HashMap::new();
+        assert!(get_encrypt_access(&Some(options.clone())).is_none());
+        options.insert(ENCRYPT_ACCESS.to_string(), json!(access).to_string());
+        assert!(get_encrypt_access(&Some(options)).unwrap().is_payment);
+    }
+
+    #[test]
+    pub fn test_schedule() {
+        let account_id = AccountId::from_str("alice.testnet").unwrap();
+        let mut scheduled = HashMap::new();
+        scheduled.insert(PUBLISH_AT.to_string(), "1".to_string());
+        let mut expired = HashMap::new();
+        expired.insert(EXPIRE_AT.to_string(), "0".to_string());
+        for options in [scheduled, expired] {
+            let options = Some(options);
+            let (target_hash, _, _) = set_content("ama".to_string(), account_id.clone(), "".to_string(), options.clone(), None);
+            let hierarchies = vec![Hierarchy { target_hash, account_id: account_id.clone(), options }];
+            assert!(utils::get_content_hash(hierarchies.clone(), None, false).is_none());
+            assert!(get_stored_content_hash(hierarchies).is_some());
+        }
+        assert!(is_expired(&Some(HashMap::from([(EXPIRE_AT.to_string(), "0".to_string())]))));
+        assert!(!is_expired(&Some(HashMap::from([(EXPIRE_AT.to_string(), "1".to_string())]))));
+        assert!(is_live(&None));
+    }
+
+    #[test]
+    pub fn test_edit_content() {
+        let mut community = setup_community(HashMap::new());
+        let author = account("alice.testnet");
+        set_context(&author);
+        let target_hash = community.add_content("post".to_string(), vec![], None);
+        let hierarchies = vec![Hierarchy { target_hash, account_id: author, options: None }];
+        let hierarchy_hash = get_stored_content_hash(hierarchies.clone()).unwrap();
+
+        set_block_timestamp(1);
+        let first = community.edit_content("fixed typo".to_string(), hierarchies.clone());
+        set_block_timestamp(2);
+        let second = community.edit_content("fixed another typo".to_string(), hierarchies.clone());
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(logs.last().unwrap().contains("ContentEdit") && logs.last().unwrap().contains(&String::from(&second)));
+
+        // both revisions replay from the original target hash and the head moved to the latest
+        assert!(first == get_revision_hash(&target_hash, "fixed typo", 1));
+        assert!(second == get_revision_hash(&first, "fixed another typo", 2));
+        let revision = get_content_revision(&hierarchy_hash).unwrap();
+        assert!(revision.head == second && revision.count.0 == 2);
+        assert_eq!(get_stored_content_hash(hierarchies).unwrap(), hierarchy_hash);
+    }
+
     #[test]
     pub fn test_like() {
         let hierarchies = vec![
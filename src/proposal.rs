@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use crate::*;
+use crate::account::ext_nft_core;
 use crate::drip::get_map_value;
 use crate::utils::{get_account, set_account};
 use ed25519_dalek::{ExpandedSecretKey, SecretKey};
@@ -284,7 +285,12 @@ impl Proposal {
                             ext_ft_core::ext(token_id.clone()).ft_transfer_call(args.receiver_id, args.amount, args.memo, args.msg).into()
                         }
                     },
-                    AssetKey::NFT(contract_id, token_id) => PromiseOrValue::Value(()),
+                    AssetKey::NFT(contract_id, token_id) => {
+                        let token_id = token_id.clone().expect("token id required");
+                        community.decrease_nft(contract_id.clone(), token_id.clone());
+                        set_account(&env::current_account_id(), &community);
+                        ext_nft_core::ext(contract_id.clone()).with_attached_deposit(1).nft_transfer(args.receiver_id, token_id, None, args.memo).into()
+                    },
                     _  => PromiseOrValue::Value(())
                 }
                 
@@ -427,8 +433,8 @@ impl Community {
                     let option = proposal.options.get(index as usize).unwrap();
                     if option.action_kind == "transfer".to_string() {
                         let args = serde_json::from_str::<Transfer>(&option.args).unwrap();
-                        community.increase_balance(args.asset, args.amount.0);
-                        self.accounts.insert(&env::current_account_id(), &community);
+                        community.refund_asset(args.asset, args.amount.0);
+                        set_account(&env::current_account_id(), &community);
                     }
                 }
                 PromiseOrValue::Value(())
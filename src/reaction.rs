@@ -0,0 +1,141 @@
+use std::collections::HashSet;
+
+use crate::*;
+use post::Hierarchy;
+use utils::{get, get_content_hash, check_and_set};
+
+fn get_default_kinds() -> HashSet<String> {
+    ["thumbs_up", "heart", "laugh", "wow", "sad", "angry"].iter().map(|v| v.to_string()).collect()
+}
+
+pub fn get_reaction_kinds() -> HashSet<String> {
+    let kinds: LazyOption<HashSet<String>> = LazyOption::new(StorageKey::ReactionKinds, None);
+    kinds.get().unwrap_or_else(get_default_kinds)
+}
+
+pub(crate) fn set_reaction_kinds(kinds: HashSet<String>) {
+    let mut stored: LazyOption<HashSet<String>> = LazyOption::new(StorageKey::ReactionKinds, None);
+    stored.set(&kinds);
+}
+
+fn get_counts() -> LookupMap<String, HashMap<String, u64>> {
+    LookupMap::new(StorageKey::ReactionCounts)
+}
+
+/// Count of each reaction kind on the content.
+pub fn get_reaction_counts(hierarchy_hash: &String) -> HashMap<String, u64> {
+    get_counts().get(hierarchy_hash).unwrap_or_default()
+}
+
+pub(crate) fn set_reaction_counts(hierarchy_hash: &String, counts: HashMap<String, u64>) {
+    if counts.is_empty() {
+        get_counts().remove(hierarchy_hash);
+    } else {
+        get_counts().insert(hierarchy_hash, &counts);
+    }
+}
+
+pub(crate) fn remove_reaction_counts(hierarchy_hash: &String) {
+    get_counts().remove(hierarchy_hash);
+}
+
+fn update_count(hierarchy_hash: &String, kind: &String, increase: bool) {
+    let mut counts = get_reaction_counts(hierarchy_hash);
+    let count = counts.entry(kind.clone()).or_insert(0);
+    *count = if increase { *count + 1 } else { count.saturating_sub(1) };
+    if *count == 0 {
+        counts.remove(kind);
+    }
+    if counts.is_empty() {
+        get_counts().remove(hierarchy_hash);
+    } else {
+        get_counts().insert(hierarchy_hash, &counts);
+    }
+}
+
+fn get_reaction_key(account_id: &AccountId, hierarchy_hash: &str) -> Vec<u8> {
+    env::sha256(&(account_id.to_string() + "react" + hierarchy_hash).into_bytes())
+}
+
+/// Active reaction of the account on the content.
+pub fn get_reaction(account_id: &AccountId, hierarchy_hash: &str) -> Option<String> {
+    get::<String>(&get_reaction_key(account_id, hierarchy_hash))
+}
+
+#[near_bindgen]
+impl Community {
+    /// Sets the sender's reaction on the content, replacing any previous one. Gated by `Permission::Like`,
+    /// drip is only paid for the first reaction of an account on a content.
+    pub fn react(&mut self, hierarchies: Vec<Hierarchy>, kind: String) {
+        init_callback();
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = get_predecessor_id();
+        assert!(self.can_execute_action(None, None, Permission::Like), "not allowed");
+        assert!(get_reaction_kinds().contains(&kind), "unknown reaction");
+        follow::assert_not_blocked(&hierarchies, &sender_id);
+        let hierarchy_hash = get_content_hash(hierarchies.clone(), None, false).expect("content not found");
+        report::assert_not_hidden(&hierarchy_hash);
+
+        let key = get_reaction_key(&sender_id, &hierarchy_hash);
+        let previous = get::<String>(&key);
+        if previous.as_ref() == Some(&kind) {
+            return
+        }
+        if let Some(previous) = previous.as_ref() {
+            update_count(&hierarchy_hash, previous, false);
+        }
+        set(&key, kind.clone());
+        update_count(&hierarchy_hash, &kind, true);
+
+        let paid = env::sha256(&(sender_id.to_string() + "reacted" + &hierarchy_hash).into_bytes());
+        let mut drips = Vec::new();
+        if !check_and_set(&paid, 0) {
+            drips = self.drip.set_react_drip(hierarchies.clone(), sender_id, &kind);
+        }
+        Event::log_react_content(
+            hierarchies,
+            kind,
+            previous,
+            Some(json!({
+                "drips": drips
+            }).to_string())
+        );
+        set_storage_usage(initial_storage_usage, None);
+    }
+
+    pub fn unreact(&mut self, hierarchies: Vec<Hierarchy>) {
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = get_predecessor_id();
+        assert!(self.can_execute_action(None, None, Permission::Unlike), "not allowed");
+        let hierarchy_hash = get_content_hash(hierarchies.clone(), None, false).expect("content not found");
+
+        let key = get_reaction_key(&sender_id, &hierarchy_hash);
+        let kind = get::<String>(&key).expect("not reacted");
+        remove(&key);
+        update_count(&hierarchy_hash, &kind, false);
+        Event::log_unreact_content(hierarchies, kind, None);
+        set_storage_usage(initial_storage_usage, None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{update_count, get_reaction_counts};
+
+    #[test]
+    pub fn test_reaction_counts() {
+        let hierarchy_hash = "5EVZZTdCcMQ6Di5fq2Zw1HuFd4chQ9KK4DG3byVwiSyp".to_string();
+        let heart = "heart".to_string();
+        let laugh = "laugh".to_string();
+        update_count(&hierarchy_hash, &heart, true);
+        update_count(&hierarchy_hash, &heart, true);
+        update_count(&hierarchy_hash, &laugh, true);
+        update_count(&hierarchy_hash, &heart, false);
+        let counts = get_reaction_counts(&hierarchy_hash);
+        assert_eq!(counts.get(&heart), Some(&1));
+        assert_eq!(counts.get(&laugh), Some(&1));
+        update_count(&hierarchy_hash, &heart, false);
+        update_count(&hierarchy_hash, &laugh, false);
+        assert!(get_reaction_counts(&hierarchy_hash).is_empty());
+    }
+}
@@ -0,0 +1,269 @@
+use crate::*;
+use near_sdk::collections::TreeMap;
+use post::Hierarchy;
+
+pub const MAX_REASON_LENGTH: usize = 256;
+
+#[derive(BorshDeserialize, BorshSerialize)]
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[derive(Debug, Clone)]
+pub struct ReportConfig {
+    pub max_reporters: u32,             //reports accepted per content before it's closed to more
+    pub hide_threshold: Option<u32>     //report count that hides the content until a moderator confirms, None never hides
+}
+
+impl Default for ReportConfig {
+    fn default() -> Self {
+        Self {
+            max_reporters: 5,
+            hide_threshold: None
+        }
+    }
+}
+
+pub fn get_report_config() -> ReportConfig {
+    let config: LazyOption<ReportConfig> = LazyOption::new(StorageKey::ReportConfig, None);
+    config.get().unwrap_or_default()
+}
+
+pub(crate) fn set_report_config(config: &ReportConfig) {
+    assert!(config.max_reporters > 0, "max_reporters must be positive");
+    if let Some(threshold) = config.hide_threshold {
+        assert!(threshold > 0 && threshold <= config.max_reporters, "hide_threshold out of range");
+    }
+    let mut stored: LazyOption<ReportConfig> = LazyOption::new(StorageKey::ReportConfig, None);
+    stored.set(config);
+}
+
+fn get_hidden_key(hierarchy_hash: &str) -> Vec<u8> {
+    env::sha256(&(hierarchy_hash.to_string() + "hidden").into_bytes())
+}
+
+pub fn is_hidden(hierarchy_hash: &str) -> bool {
+    utils::check(&get_hidden_key(hierarchy_hash))
+}
+
+pub(crate) fn set_hidden(hierarchy_hash: &str) {
+    set(&get_hidden_key(hierarchy_hash), 0);
+}
+
+/// Returns false if the content wasn't hidden.
+pub(crate) fn unhide(hierarchy_hash: &str) -> bool {
+    let key = get_hidden_key(hierarchy_hash);
+    if !utils::check(&key) {
+        return false
+    }
+    remove(&key);
+    true
+}
+
+/// Hidden content takes no replies or likes until a moderator resolves its reports.
+pub(crate) fn assert_not_hidden(hierarchy_hash: &str) {
+    assert!(!is_hidden(hierarchy_hash), "content hidden");
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[derive(Debug, Clone)]
+pub struct ReportReason {
+    pub reason: String,
+    pub timestamp: U64
+}
+
+/// Kept next to `Community.reports`, which stays the record of who reported and holds a deposit.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[derive(Debug, Clone)]
+pub struct ReportDetail {
+    pub hierarchies: Vec<Hierarchy>,
+    pub reasons: HashMap<AccountId, ReportReason>
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[derive(Debug, Clone)]
+pub struct ReporterOutput {
+    pub account_id: AccountId,
+    pub reason: Option<String>,
+    pub timestamp: Option<U64>
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[derive(Debug, Clone)]
+pub struct ReportOutput {
+    pub hierarchy_hash: Base58CryptoHash,
+    /// None for reports made before hierarchies were kept
+    pub hierarchies: Option<Vec<Hierarchy>>,
+    pub report_count: u32,
+    pub hidden: bool,
+    pub reporters: Vec<ReporterOutput>
+}
+
+fn get_details() -> LookupMap<Base58CryptoHash, ReportDetail> {
+    LookupMap::new(StorageKey::ReportDetails)
+}
+
+pub fn get_report_detail(hierarchy_hash: &Base58CryptoHash) -> Option<ReportDetail> {
+    get_details().get(hierarchy_hash)
+}
+
+pub(crate) fn add_report_reason(hierarchy_hash: &Base58CryptoHash, hierarchies: &[Hierarchy], account_id: &AccountId, reason: String) {
+    assert!(reason.len() <= MAX_REASON_LENGTH, "reason too long");
+    let mut detail = get_report_detail(hierarchy_hash).unwrap_or_else(|| ReportDetail {
+        hierarchies: hierarchies.to_vec(),
+        reasons: HashMap::new()
+    });
+    detail.reasons.insert(account_id.clone(), ReportReason {
+        reason,
+        timestamp: env::block_timestamp().into()
+    });
+    get_details().insert(hierarchy_hash, &detail);
+}
+
+pub(crate) fn remove_report_reason(hierarchy_hash: &Base58CryptoHash, account_id: &AccountId) {
+    let mut detail = match get_report_detail(hierarchy_hash) {
+        Some(v) => v,
+        None => return
+    };
+    detail.reasons.remove(account_id);
+    if detail.reasons.is_empty() {
+        get_details().remove(hierarchy_hash);
+    } else {
+        get_details().insert(hierarchy_hash, &detail);
+    }
+}
+
+pub(crate) fn remove_report_detail(hierarchy_hash: &Base58CryptoHash) {
+    get_details().remove(hierarchy_hash);
+}
+
+pub fn get_report_output(hierarchy_hash: Base58CryptoHash, accounts: HashSet<AccountId>) -> ReportOutput {
+    let detail = get_report_detail(&hierarchy_hash);
+    let mut reporters: Vec<ReporterOutput> = accounts.into_iter().map(|account_id| {
+        let reason = detail.as_ref().and_then(|v| v.reasons.get(&account_id)).cloned();
+        ReporterOutput {
+            account_id,
+            reason: reason.as_ref().map(|v| v.reason.clone()),
+            timestamp: reason.map(|v| v.timestamp)
+        }
+    }).collect();
+    reporters.sort_by_key(|v| v.timestamp.map(|t| t.0));
+    ReportOutput {
+        hidden: is_hidden(&String::from(&hierarchy_hash)),
+        hierarchy_hash,
+        hierarchies: detail.map(|v| v.hierarchies),
+        report_count: reporters.len() as u32,
+        reporters
+    }
+}
+
+/// Inverted report count, first report timestamp and content hash,
+/// so the queue iterates most reported content first and ties go to the earliest first report.
+type QueueKey = (u32, u64, CryptoHash);
+
+// the tree keeps its root and size in the struct, so it's stored whole after every change
+fn get_queue() -> TreeMap<QueueKey, ()> {
+    let queue: LazyOption<TreeMap<QueueKey, ()>> = LazyOption::new(StorageKey::ReportQueue, None);
+    queue.get().unwrap_or_else(|| TreeMap::new(StorageKey::ReportQueueTree))
+}
+
+fn set_queue(queue: &TreeMap<QueueKey, ()>) {
+    let mut stored: LazyOption<TreeMap<QueueKey, ()>> = LazyOption::new(StorageKey::ReportQueue, None);
+    stored.set(queue);
+}
+
+/// Where each reported content currently sits in the queue, so it can be moved without a scan.
+fn get_queue_keys() -> LookupMap<Base58CryptoHash, QueueKey> {
+    LookupMap::new(StorageKey::ReportQueueKeys)
+}
+
+/// Called after the reporters of a content change, empty `accounts` drops it from the queue.
+pub(crate) fn set_queue_entry(hierarchy_hash: &Base58CryptoHash, accounts: &HashSet<AccountId>) {
+    remove_queue_entry(hierarchy_hash);
+    if accounts.is_empty() {
+        return
+    }
+    let detail = get_report_detail(hierarchy_hash);
+    // reports made before reasons were kept have no timestamp and go first among their count
+    let first_report = accounts.iter()
+        .map(|account_id| detail.as_ref().and_then(|v| v.reasons.get(account_id)).map_or(0, |v| v.timestamp.0))
+        .min()
+        .unwrap_or(0);
+    let key = (u32::MAX - accounts.len() as u32, first_report, CryptoHash::from(*hierarchy_hash));
+    let mut queue = get_queue();
+    queue.insert(&key, &());
+    set_queue(&queue);
+    get_queue_keys().insert(hierarchy_hash, &key);
+}
+
+pub(crate) fn remove_queue_entry(hierarchy_hash: &Base58CryptoHash) {
+    if let Some(key) = get_queue_keys().remove(hierarchy_hash) {
+        let mut queue = get_queue();
+        queue.remove(&key);
+        set_queue(&queue);
+    }
+}
+
+pub fn get_queued_reports(from: u64, limit: u64) -> Vec<Base58CryptoHash> {
+    get_queue().iter().skip(from as usize).take(limit as usize).map(|(key, _)| Base58CryptoHash::from(key.2)).collect()
+}
+
+#[near_bindgen]
+impl Community {
+    /// Queues reports made before the report queue was kept, `from` and `limit` page through all reported content.
+    pub fn sync_report_queue(&mut self, from: u64, limit: u64) {
+        let initial_storage_usage = env::storage_usage();
+        let reports: Vec<(Base58CryptoHash, HashSet<AccountId>)> = self.reports.iter().skip(from as usize).take(limit as usize).collect();
+        for (hierarchy_hash, accounts) in reports {
+            set_queue_entry(&hierarchy_hash, &accounts);
+        }
+        set_storage_usage(initial_storage_usage, None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, str::FromStr};
+
+    use near_sdk::{AccountId, json_types::Base58CryptoHash};
+
+    use super::{add_report_reason, remove_report_reason, get_report_output, set_queue_entry, remove_queue_entry, get_queued_reports, set_hidden, unhide, is_hidden};
+
+    #[test]
+    pub fn test_report_queue() {
+        let alice = AccountId::from_str("alice.testnet").unwrap();
+        let bob = AccountId::from_str("bob.testnet").unwrap();
+        let first = Base58CryptoHash::from([1u8; 32]);
+        let second = Base58CryptoHash::from([2u8; 32]);
+        add_report_reason(&first, &[], &alice, "spam".to_string());
+        add_report_reason(&second, &[], &alice, "abuse".to_string());
+        add_report_reason(&second, &[], &bob, "abuse".to_string());
+
+        set_queue_entry(&first, &vec![alice.clone()].into_iter().collect());
+        set_queue_entry(&second, &vec![alice.clone()].into_iter().collect());
+        assert_eq!(get_queued_reports(0, 10), vec![first, second]);
+        set_queue_entry(&second, &vec![alice.clone(), bob.clone()].into_iter().collect());
+        assert_eq!(get_queued_reports(0, 10), vec![second, first]);
+        assert_eq!(get_queued_reports(1, 10), vec![first]);
+        let report = get_report_output(second, vec![alice.clone(), bob.clone()].into_iter().collect());
+        assert_eq!(report.report_count, 2);
+        assert_eq!(get_report_output(first, vec![alice.clone()].into_iter().collect()).reporters[0].reason, Some("spam".to_string()));
+        remove_queue_entry(&second);
+        set_queue_entry(&first, &HashSet::new());
+        assert!(get_queued_reports(0, 10).is_empty());
+
+        remove_report_reason(&second, &bob);
+        let report = get_report_output(second, vec![bob].into_iter().collect::<HashSet<_>>());
+        assert!(report.reporters[0].reason.is_none());
+        assert!(!report.hidden);
+
+        let hierarchy_hash = String::from(&first);
+        set_hidden(&hierarchy_hash);
+        assert!(get_report_output(first, vec![alice].into_iter().collect()).hidden);
+        assert!(unhide(&hierarchy_hash));
+        assert!(!unhide(&hierarchy_hash));
+        assert!(!is_hidden(&hierarchy_hash));
+    }
+}
@@ -1,11 +1,14 @@
 use near_non_transferable_token::fungible_token::receiver::FungibleTokenReceiver as NtftReceiver;
 
 use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver as FtReceiver;
+use near_contract_standards::non_fungible_token::core::NonFungibleTokenReceiver as NftReceiver;
+use near_contract_standards::non_fungible_token::TokenId;
 
 use crate::{*, utils::set_account};
 use crate::account::{AssetKey, Condition};
 use crate::drip::get_map_value;
 use crate::utils::{get_parent_contract_id, get_content_hash};
+use crate::post::{get_encrypt_access, get_decrypt_receipt, set_decrypt_receipt};
 use near_sdk::{PromiseOrValue, PromiseResult};
 
 #[derive(Serialize, Deserialize)]
@@ -45,7 +48,7 @@ impl Community {
             },
             PromiseResult::Failed => {
                 let mut account = get_account(&account_id).registered();
-                account.increase_balance(asset, amount.0);
+                account.refund_asset(asset, amount.0);
                 set_account(&account_id, &account);
                 PromiseOrValue::Value(())
             },
@@ -78,6 +81,31 @@ impl FtReceiver for Community {
 }
 
 
+#[near_bindgen]
+impl NftReceiver for Community {
+
+    fn nft_on_transfer(&mut self, sender_id: AccountId, previous_owner_id: AccountId, token_id: TokenId, msg: String) -> PromiseOrValue<bool> {
+        let msg_input = serde_json::from_str(&msg).unwrap();
+        match msg_input {
+            MsgInput::Deposit => {
+                let mut account = get_account(&previous_owner_id);
+                account.increase_nft(get_predecessor_id(), token_id);
+                set_account(&previous_owner_id, &account);
+                PromiseOrValue::Value(false)
+            },
+            MsgInput::Donate => {
+                let mut account = get_account(&env::current_account_id()).registered();
+                account.increase_nft(get_predecessor_id(), token_id);
+                set_account(&env::current_account_id(), &account);
+                PromiseOrValue::Value(false)
+            },
+            _ => {PromiseOrValue::Value(true)}
+        }
+    }
+
+}
+
+
 #[near_bindgen]
 impl NtftReceiver for Community {
 
@@ -85,18 +113,17 @@ impl NtftReceiver for Community {
         let msg_input: MsgInput = serde_json::from_str(&msg).unwrap();
         match msg_input {
             MsgInput::Report(report_input) => {
-                assert!(get_arg::<AccountId>(DRIP_CONTRACT).unwrap_or(AccountId::new_unchecked("".to_string())) == get_predecessor_id(), "wrong token id");
+                assert!(get_arg::<AccountId>(DRIP_CONTRACT) == Some(get_predecessor_id()), "wrong token id");
                 assert!(contract_id == env::current_account_id(), "wrong drip");
                 let need_amount = get_map_value(&"report_deposit".to_string());
                 assert!(amount.0 >= need_amount, "not enough drip");
-                self.internal_report(owner_id, report_input.hierarchies);
+                self.internal_report(owner_id, report_input.hierarchies, report_input.reason);
                 PromiseOrValue::Value((amount.0 - need_amount).into())
             },
             _ => {
-                let mut accounts: LookupMap<AccountId, Account> = LookupMap::new(StorageKey::Account);
                 let mut account = get_account(&owner_id);
                 account.increase_balance(AssetKey::Drip((Some(get_predecessor_id()), contract_id.clone())), amount.0);
-                accounts.insert(&owner_id, &account);
+                set_account(&owner_id, &account);
                 PromiseOrValue::Value(0.into())
             }
         }
@@ -111,7 +138,7 @@ impl NtftReceiver for Community {
         let msg_input: MsgInput = serde_json::from_str(&msg).unwrap();
         let promise = match msg_input {
             MsgInput::RevokeReport(report_input) => {
-                assert!(get_arg::<AccountId>(DRIP_CONTRACT).unwrap_or(AccountId::new_unchecked("".to_string())) == get_predecessor_id(), "wrong token id");
+                assert!(get_arg::<AccountId>(DRIP_CONTRACT) == Some(get_predecessor_id()), "wrong token id");
                 assert!(contract_id == env::current_account_id(), "wrong drip");
                 let need_amount = get_map_value(&"report_deposit".to_string());
                 assert!(amount.0 > need_amount, "not enough amount");
@@ -131,25 +158,31 @@ impl NtftReceiver for Community {
         let msg_input: MsgInput = serde_json::from_str(&msg).unwrap();
         match msg_input {
             MsgInput::Decrypt(hierarchies) => {
-                assert!(get_arg::<AccountId>(DRIP_CONTRACT).unwrap_or(AccountId::new_unchecked("".to_string())) == get_predecessor_id(), "wrong token id");
+                assert!(get_arg::<AccountId>(DRIP_CONTRACT) == Some(get_predecessor_id()), "wrong token id");
                 assert!(contract_id == env::current_account_id(), "wrong drip");
-                assert!(get_content_hash(hierarchies.clone(), None, true).is_some(), "content not found");
+                let initial_storage_usage = env::storage_usage();
+                let hierarchy_hash = get_content_hash(hierarchies.clone(), None, false).expect("content not found");
                 let hierarchy = hierarchies.get(&hierarchies.len() - 1).unwrap();
-                let options = hierarchy.options.clone().unwrap();
-                let access = serde_json::from_str::<Access>(options.get("access").unwrap()).unwrap();
+                let access = get_encrypt_access(&hierarchy.options).expect("not encrypted");
                 assert!(access.is_payment, "not for burning");
-                let mut need_amount = 0;
-                match access.condition {
-                    Condition::DripCondition(drip_condition) => {
-                        if let Some(token_id) = drip_condition.token_id.clone() {
-                            if token_id == env::predecessor_account_id() && contract_id == contract_id {
-                                need_amount += drip_condition.amount_to_access.0;
-                            }
-                        }
-                    }
-                    _ => {}
-                }
+                assert!(get_decrypt_receipt(&owner_id, &hierarchy_hash).is_none(), "already paid");
+                let need_amount = get_burn_amount(&access.condition, &get_predecessor_id(), &contract_id).expect("not for burning");
+                assert!(need_amount > 0, "not for burning");
                 assert!(amount.0 >= need_amount, "not enough drip");
+                let until = set_decrypt_receipt(&owner_id, &hierarchy_hash, &access);
+                // burned drip goes to the author as revenue
+                let drips = self.drip.set_custom_drip("decrypt".to_string(), &hierarchy.account_id, need_amount, false);
+                set_storage_usage(initial_storage_usage, Some(owner_id.clone()));
+                Event::log_other(
+                    Some(json!({
+                        "decrypt": {
+                            "account_id": owner_id,
+                            "hierarchies": hierarchies,
+                            "until": U64::from(until)
+                        },
+                        "drips": drips
+                    }).to_string())
+                );
                 PromiseOrValue::Value((amount.0 - need_amount).into())
             },
             _ => {PromiseOrValue::Value(amount)}
@@ -160,12 +193,43 @@ impl NtftReceiver for Community {
 }
 
 
+/// Drip of `token_id` to burn for the condition, `None` if it can't be met by burning that drip.
+/// An `Or` costs its cheapest burnable branch, an `And` needs every branch burnable.
+pub(crate) fn get_burn_amount(condition: &Condition, token_id: &AccountId, contract_id: &AccountId) -> Option<u128> {
+    match condition {
+        Condition::DripCondition(drip_condition) => {
+            match drip_condition.token_id.as_ref() {
+                Some(drip_token_id) if drip_token_id == token_id && drip_condition.contract_id == *contract_id => Some(drip_condition.amount_to_access.0),
+                _ => None
+            }
+        },
+        Condition::CompositeCondition(composite) => {
+            let amounts = composite.items.iter().map(|item| get_burn_amount(&item.condition, token_id, contract_id));
+            match composite.relationship {
+                Relationship::And => amounts.sum(),
+                Relationship::Or => amounts.flatten().min()
+            }
+        },
+        _ => None
+    }
+}
+
+
 #[cfg(test)]
 mod test {
     use std::{collections::HashMap, convert::{TryInto, TryFrom}, str::FromStr};
 
     use near_sdk::{serde_json::{json, self}, AccountId, serde::{Deserialize, de::IntoDeserializer}};
 
+    use near_non_transferable_token::fungible_token::receiver::FungibleTokenReceiver;
+    use near_sdk::{PromiseOrValue, json_types::U128};
+
+    use crate::account::{Access, Condition, CompositeCondition, DripCondition, FTCondition, Relationship};
+    use crate::Community;
+    use crate::post::{Hierarchy, ENCRYPT_ACCESS, get_decrypt_receipt, get_content_revision};
+    use crate::role::Permission;
+    use crate::test_utils::{setup_community, set_context, account};
+    use crate::utils::{get_account, get_content_hash};
     use super::{MsgInput, ReportInput};
 
     fn get_arg<T>(key: &str) -> Option<T> 
@@ -195,6 +259,103 @@ mod test {
 
     }
 
+    fn get_encrypt_options(condition: Condition) -> HashMap<String, String> {
+        let access = Access {
+            condition,
+            expire_duration: None,
+            is_payment: true,
+            options: None
+        };
+        let mut options = HashMap::new();
+        options.insert(ENCRYPT_ACCESS.to_string(), json!(access).to_string());
+        options
+    }
+
+    fn get_drip_condition(amount: u128) -> Condition {
+        Condition::DripCondition(DripCondition {
+            token_id: Some(account("drip.testnet")),
+            contract_id: account("community.testnet"),
+            amount_to_access: U128::from(amount)
+        })
+    }
+
+    #[test]
+    fn test_decrypt() {
+        let mut args = HashMap::new();
+        args.insert("drip_contract".to_string(), "drip.testnet".to_string());
+        let mut community = setup_community(args);
+        let author = account("alice.testnet");
+        let reader = account("bob.testnet");
+
+        // the FT branch can't be bought by burning, the drip branch sets the price
+        let options = get_encrypt_options(Condition::CompositeCondition(CompositeCondition {
+            relationship: Relationship::Or,
+            items: vec![
+                Access {
+                    condition: Condition::FTCondition(FTCondition { token_id: account("usdt.testnet"), amount_to_access: U128::from(1) }),
+                    expire_duration: None,
+                    is_payment: true,
+                    options: None
+                },
+                Access { condition: get_drip_condition(100), expire_duration: None, is_payment: true, options: None }
+            ]
+        }));
+        set_context(&author);
+        let target_hash = community.add_encrypt_content("secret".to_string(), vec![], options.clone());
+        let hierarchies = vec![Hierarchy { target_hash, account_id: author.clone(), options: Some(options) }];
+        let hierarchy_hash = get_content_hash(hierarchies.clone(), None, false).unwrap();
+        let author_drip = get_account(&author).get_drip();
+
+        set_context(&account("drip.testnet"));
+        let msg = json!(MsgInput::Decrypt(hierarchies)).to_string();
+        let refund = match community.ft_on_burn(reader.clone(), account("community.testnet"), U128::from(150), msg) {
+            PromiseOrValue::Value(v) => v.0,
+            _ => unreachable!()
+        };
+        assert_eq!(refund, 50);
+        assert_eq!(get_decrypt_receipt(&reader, &hierarchy_hash), Some(u64::MAX));
+        assert_eq!(get_account(&author).get_drip(), author_drip + 100);
+    }
+
+    /// Alice's encrypted post after `permission` was taken out of the global permissions.
+    fn setup_encrypted_without(permission: Permission) -> (Community, Vec<Hierarchy>) {
+        let mut args = HashMap::new();
+        args.insert("drip_contract".to_string(), "drip.testnet".to_string());
+        let mut community = setup_community(args);
+        let author = account("alice.testnet");
+        let options = get_encrypt_options(get_drip_condition(100));
+        set_context(&author);
+        let target_hash = community.add_encrypt_content("secret".to_string(), vec![], options.clone());
+        community.role_management.global_role.remove(&permission);
+        (community, vec![Hierarchy { target_hash, account_id: author, options: Some(options) }])
+    }
+
+    #[test]
+    fn test_edit_encrypted() {
+        let (mut community, hierarchies) = setup_encrypted_without(Permission::AddContent(0));
+        community.edit_content("new secret".to_string(), hierarchies.clone());
+        let hierarchy_hash = get_content_hash(hierarchies, None, false).unwrap();
+        assert_eq!(get_content_revision(&hierarchy_hash).unwrap().count.0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "not allowed")]
+    fn test_edit_encrypted_denied() {
+        let (mut community, hierarchies) = setup_encrypted_without(Permission::AddEncryptContent(0));
+        community.edit_content("new secret".to_string(), hierarchies);
+    }
+
+    #[test]
+    #[should_panic(expected = "paid access must be bought by burning drip")]
+    fn test_encrypt_unburnable() {
+        let mut args = HashMap::new();
+        args.insert("drip_contract".to_string(), "drip.testnet".to_string());
+        let mut community = setup_community(args);
+        let options = get_encrypt_options(Condition::FTCondition(FTCondition { token_id: account("usdt.testnet"), amount_to_access: U128::from(1) }));
+        set_context(&account("alice.testnet"));
+        community.add_encrypt_content("secret".to_string(), vec![], options);
+    }
+
     #[test]
     fn test_args() {
         print!("{}", get_arg::<AccountId>("drip_contract").unwrap().to_string());
@@ -18,6 +18,7 @@ use near_sdk::{env, AccountId, Balance};
 
 // member keys:
 //   until: U64,     for time limit groups
+pub const MEMBER_UNTIL: &str = "until";
 
 #[derive(BorshSerialize, BorshDeserialize)]
 #[derive(Serialize, Deserialize)]
@@ -32,6 +33,19 @@ pub struct Role {
     pub override_level: u32    // can override lower level group permissions, like black list
 }
 
+/// Changes to a role for `set_role`, fields left out are kept.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[derive(Debug, Clone, Default)]
+pub struct RoleUpdate {
+    pub alias: Option<String>,
+    pub permissions: Option<Vec<Permission>>,
+    pub mod_level: Option<u32>,
+    pub override_level: Option<u32>,
+    pub remove_permissions: Option<Vec<Permission>>,
+    pub replace_permissions: Option<Vec<Permission>>
+}
+
 #[derive(BorshSerialize, BorshDeserialize)]
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
@@ -58,8 +72,180 @@ pub enum Permission {
 
     AddProposal(bool),  //false for no action proposal
     Vote,
+    PinContent,
+}
+
+
+/// Iterable account index under a storage prefix, e.g. role members kept next to the member map under the role's `members` prefix.
+pub struct MemberIndex {
+    prefix: Vec<u8>
+}
+
+impl MemberIndex {
+    pub fn new(prefix: &[u8]) -> Self {
+        Self { prefix: prefix.to_vec() }
+    }
+
+    fn len_key(&self) -> Vec<u8> {
+        [self.prefix.clone(), b"_len".to_vec()].concat()
+    }
+
+    fn accounts(&self) -> LookupMap<u64, AccountId> {
+        LookupMap::new([self.prefix.clone(), b"_idx".to_vec()].concat())
+    }
+
+    fn positions(&self) -> LookupMap<AccountId, u64> {
+        LookupMap::new([self.prefix.clone(), b"_pos".to_vec()].concat())
+    }
+
+    pub fn len(&self) -> u64 {
+        match env::storage_read(&self.len_key()) {
+            Some(v) => u64::try_from_slice(&v).unwrap(),
+            None => 0
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn set_len(&self, len: u64) {
+        if len == 0 {
+            env::storage_remove(&self.len_key());
+        } else {
+            env::storage_write(&self.len_key(), &len.try_to_vec().unwrap());
+        }
+    }
+
+    pub fn contains(&self, account_id: &AccountId) -> bool {
+        self.positions().contains_key(account_id)
+    }
+
+    pub fn insert(&mut self, account_id: &AccountId) {
+        if self.contains(account_id) {
+            return
+        }
+        let len = self.len();
+        self.accounts().insert(&len, account_id);
+        self.positions().insert(account_id, &len);
+        self.set_len(len + 1);
+    }
+
+    pub fn remove(&mut self, account_id: &AccountId) {
+        let mut positions = self.positions();
+        let index = match positions.remove(account_id) {
+            Some(index) => index,
+            None => return
+        };
+        let mut accounts = self.accounts();
+        let last = self.len() - 1;
+        let last_account_id = accounts.remove(&last).unwrap();
+        if index != last {
+            accounts.insert(&index, &last_account_id);
+            positions.insert(&last_account_id, &index);
+        }
+        self.set_len(last);
+    }
+
+    pub fn get(&self, from: u64, limit: u64) -> Vec<AccountId> {
+        let accounts = self.accounts();
+        (from..std::cmp::min(from.saturating_add(limit), self.len())).filter_map(|index| accounts.get(&index)).collect()
+    }
+}
+
+impl Role {
+    fn get_members(&self) -> LookupMap<AccountId, HashMap<String, String>> {
+        LookupMap::new(self.members.clone())
+    }
+
+    pub fn get_member_index(&self) -> MemberIndex {
+        MemberIndex::new(&self.members)
+    }
+
+    pub fn add_member(&self, account_id: &AccountId, options: &HashMap<String, String>) {
+        self.get_members().insert(account_id, options);
+        self.get_member_index().insert(account_id);
+    }
+
+    pub fn remove_member(&self, account_id: &AccountId) {
+        self.get_members().remove(account_id);
+        self.get_member_index().remove(account_id);
+    }
+
+    /// Drops up to `limit` indexed members and returns how many are left,
+    /// members added before the index existed have to be synced first.
+    pub fn clear_members(&self, limit: u64) -> u64 {
+        let index = self.get_member_index();
+        let len = index.len();
+        for account_id in index.get(len.saturating_sub(limit), limit) {
+            self.remove_member(&account_id);
+        }
+        len.saturating_sub(limit)
+    }
+
+    /// Member options, `None` for non members and memberships past their `until`.
+    pub fn get_member(&self, account_id: &AccountId) -> Option<HashMap<String, String>> {
+        let options = self.get_members().get(account_id)?;
+        if is_expired_member(&options) {
+            return None
+        }
+        Some(options)
+    }
+
+    pub fn is_member(&self, account_id: &AccountId) -> bool {
+        self.get_member(account_id).is_some()
+    }
 }
 
+/// Removed roles whose members are still being cleared, keyed by role hash.
+fn get_removed_roles() -> LookupMap<String, Role> {
+    LookupMap::new(StorageKey::RemovedRoles)
+}
+
+/// Permissions explicitly denied to members of a role, keyed by role hash.
+fn get_role_denies() -> LookupMap<String, HashSet<Permission>> {
+    LookupMap::new(StorageKey::RoleDeny)
+}
+
+pub fn get_role_deny(hash: &String) -> HashSet<Permission> {
+    get_role_denies().get(hash).unwrap_or_default()
+}
+
+/// Content levels past the default depth have no permission entries of their own,
+/// they fall back to the deepest default level unless set explicitly.
+pub fn get_content_fallback(permission: &Permission) -> Option<Permission> {
+    match permission {
+        Permission::AddContent(level) if *level as usize >= MAX_LEVEL => Some(Permission::AddContent((MAX_LEVEL - 1) as u8)),
+        Permission::AddEncryptContent(level) if *level as usize >= MAX_LEVEL => Some(Permission::AddEncryptContent((MAX_LEVEL - 1) as u8)),
+        _ => None
+    }
+}
+
+/// A denied `None` variant covers every role or function of that kind.
+/// Content levels past the default depth are denied with their fallback, unless the role allows the level itself.
+pub fn check_denied(permission: &Permission, denied: &HashSet<Permission>, allowed: &HashSet<Permission>) -> bool {
+    if denied.contains(permission) {
+        return true
+    }
+    if let Some(fallback) = get_content_fallback(permission) {
+        return denied.contains(&fallback) && !allowed.contains(permission)
+    }
+    match permission {
+        Permission::SetRole(_) => denied.contains(&Permission::SetRole(None)),
+        Permission::DelRole(_) => denied.contains(&Permission::DelRole(None)),
+        Permission::AddMember(_) => denied.contains(&Permission::AddMember(None)),
+        Permission::RemoveMember(_) => denied.contains(&Permission::RemoveMember(None)),
+        Permission::Other(_) => denied.contains(&Permission::Other(None)),
+        _ => false
+    }
+}
+
+fn is_expired_member(options: &HashMap<String, String>) -> bool {
+    match options.get(MEMBER_UNTIL) {
+        Some(until) => until.parse::<u64>().unwrap_or(0) <= env::block_timestamp(),
+        None => false
+    }
+}
 
 #[derive(BorshSerialize, BorshDeserialize)]
 #[derive(Debug)]
@@ -75,31 +261,44 @@ pub struct OldRoleManagement {
     pub global_role: HashMap<Permission, (Relationship, Option<OldAccess>)>,
 }
 
+/// Global entries a new community starts with.
+pub(crate) fn get_default_global_permissions() -> HashMap<Permission, (Relationship, Option<Access>)> {
+    let mut global_permissions = HashMap::new();
+    global_permissions.insert(Permission::AddContent(0), (Relationship::Or, None));
+    global_permissions.insert(Permission::AddContent(1), (Relationship::Or, None));
+    global_permissions.insert(Permission::AddContent(2), (Relationship::Or, None));
+    global_permissions.insert(Permission::DelContent, (Relationship::Or, None));
+    global_permissions.insert(Permission::AddEncryptContent(0), (Relationship::Or, None));
+    global_permissions.insert(Permission::AddEncryptContent(1), (Relationship::Or, None));
+    global_permissions.insert(Permission::AddEncryptContent(2), (Relationship::Or, None));
+    global_permissions.insert(Permission::DelEncryptContent, (Relationship::Or, None));
+    global_permissions.insert(Permission::Like, (Relationship::Or, None));
+    global_permissions.insert(Permission::Unlike, (Relationship::Or, None));
+    global_permissions.insert(Permission::Share, (Relationship::Or, None));
+    global_permissions.insert(Permission::Report, (Relationship::Or, None));
+    global_permissions.insert(Permission::Vote, (Relationship::Or, None));
+    global_permissions.insert(Permission::AddProposal(false), (Relationship::Or, None));
+
+    global_permissions.insert(Permission::AddProposal(true), (Relationship::And, None));
+    global_permissions.insert(Permission::ReportConfirm, (Relationship::And, None));
+    global_permissions.insert(Permission::DelOthersContent, (Relationship::And, None));
+    global_permissions.insert(Permission::PinContent, (Relationship::And, None));
+    global_permissions.insert(Permission::SetRole(None), (Relationship::And, None));
+    global_permissions.insert(Permission::DelRole(None), (Relationship::And, None));
+    global_permissions.insert(Permission::AddMember(None), (Relationship::And, None));
+    global_permissions.insert(Permission::RemoveMember(None), (Relationship::And, None));
+    global_permissions.insert(Permission::Other(None), (Relationship::And, None));
+    global_permissions
+}
+
+/// Permissions the seeded "mod" role got after communities were already deployed with it.
+pub(crate) fn get_later_mod_permissions() -> Vec<Permission> {
+    vec![Permission::PinContent]
+}
+
 impl RoleManagement {
     pub fn new() -> Self {
-        let mut global_permissions = HashMap::new();
-        global_permissions.insert(Permission::AddContent(0), (Relationship::Or, None));
-        global_permissions.insert(Permission::AddContent(1), (Relationship::Or, None));
-        global_permissions.insert(Permission::AddContent(2), (Relationship::Or, None));
-        global_permissions.insert(Permission::DelContent, (Relationship::Or, None));
-        global_permissions.insert(Permission::AddEncryptContent(0), (Relationship::Or, None));
-        global_permissions.insert(Permission::AddEncryptContent(1), (Relationship::Or, None));
-        global_permissions.insert(Permission::AddEncryptContent(2), (Relationship::Or, None));
-        global_permissions.insert(Permission::DelEncryptContent, (Relationship::Or, None));
-        global_permissions.insert(Permission::Like, (Relationship::Or, None));
-        global_permissions.insert(Permission::Unlike, (Relationship::Or, None));
-        global_permissions.insert(Permission::Report, (Relationship::Or, None));
-        global_permissions.insert(Permission::Vote, (Relationship::Or, None));
-        global_permissions.insert(Permission::AddProposal(false), (Relationship::Or, None));
-
-        global_permissions.insert(Permission::AddProposal(true), (Relationship::And, None));
-        global_permissions.insert(Permission::ReportConfirm, (Relationship::And, None));
-        global_permissions.insert(Permission::DelOthersContent, (Relationship::And, None));
-        global_permissions.insert(Permission::SetRole(None), (Relationship::And, None));
-        global_permissions.insert(Permission::DelRole(None), (Relationship::And, None));
-        global_permissions.insert(Permission::AddMember(None), (Relationship::And, None));
-        global_permissions.insert(Permission::RemoveMember(None), (Relationship::And, None));
-        global_permissions.insert(Permission::Other(None), (Relationship::And, None));
+        let global_permissions = get_default_global_permissions();
         let mut this = Self {
             roles: HashMap::new(),
             global_role: global_permissions.clone()
@@ -128,6 +327,7 @@ impl RoleManagement {
         mod_permissions.insert(Permission::AddProposal(true));
         mod_permissions.insert(Permission::ReportConfirm);
         mod_permissions.insert(Permission::DelOthersContent);
+        mod_permissions.insert(Permission::PinContent);
         mod_permissions.insert(Permission::SetRole(None));
         mod_permissions.insert(Permission::DelRole(None));
         mod_permissions.insert(Permission::AddMember(None));
@@ -163,6 +363,7 @@ impl Community {
         let sender_id = get_predecessor_id();
         assert!(self.can_execute_action(None, None, Permission::SetRole(None)), "not allowed");
         let hash = bs58::encode(env::sha256((alias.clone() + &env::block_timestamp().to_string()).as_bytes())).into_string();
+        assert!(!get_removed_roles().contains_key(&hash), "role already exist");
         let mut role = match self.role_management.roles.get(&hash) {
             Some(v) => panic!("role already exist"),
             None => Role {
@@ -186,7 +387,11 @@ impl Community {
     }
 
 
-    pub fn set_role(&mut self, hash: Base58CryptoHash, alias: Option<String>, permissions: Option<Vec<Permission>>, mod_level: Option<u32>, override_level: Option<u32>) {
+    /// `replace_permissions` swaps the whole set before `permissions` are added and `remove_permissions` are taken away.
+    /// Every newly granted permission must be held by the sender, so a role can't be raised above its editor,
+    /// and `mod_level` and `override_level` are only changed to values below the sender's own.
+    pub fn set_role(&mut self, hash: Base58CryptoHash, update: RoleUpdate) {
+        let RoleUpdate { alias, permissions, mod_level, override_level, remove_permissions, replace_permissions } = update;
         let initial_storage_usage = env::storage_usage();
         let sender_id = get_predecessor_id();
         let hash = String::from(&hash);
@@ -207,24 +412,73 @@ impl Community {
         }
 
         if let Some(override_level) = override_level {
-            role.override_level = override_level;
+            if override_level < self.get_user_override_level(&sender_id) {
+                role.override_level = override_level;
+            }
         }
         
+        let mut new_permissions = role.permissions.clone();
+        if let Some(replace_permissions) = replace_permissions {
+            new_permissions = replace_permissions.into_iter().collect();
+        }
         if let Some(permissions) = permissions {
             for permission in permissions {
-                role.permissions.insert(permission);
+                new_permissions.insert(permission);
+            }
+        }
+        if let Some(remove_permissions) = remove_permissions {
+            for permission in remove_permissions.iter() {
+                new_permissions.remove(permission);
             }
         }
+        for permission in new_permissions.iter() {
+            if !role.permissions.contains(permission) {
+                assert!(self.can_execute_action(None, None, permission.clone()), "not allowed");
+            }
+        }
+        role.permissions = new_permissions;
         self.role_management.roles.insert(hash, role);
         set_storage_usage(initial_storage_usage, None);
     }
 
+    /// Members are cleared by `clear_role_members` once the role is gone.
+    /// Members added before roles kept a member index have to be indexed with `sync_role_members` before removing the role,
+    /// otherwise they are left in storage.
     pub fn remove_role(&mut self, hash: String) {
         let initial_storage_usage = env::storage_usage();
         Base58CryptoHash::try_from(hash.clone()).unwrap();    //exclude "all" and "ban"
         let sender_id = get_predecessor_id();
         assert!(self.can_execute_action(None, None, Permission::DelRole(Some(hash.clone()))), "not allowed");
-        self.role_management.roles.remove(&hash);
+        if let Some(role) = self.role_management.roles.remove(&hash) {
+            if !role.get_member_index().is_empty() {
+                get_removed_roles().insert(&hash, &role);
+            }
+            get_role_denies().remove(&hash);
+        }
+        set_storage_usage(initial_storage_usage, None);
+    }
+
+    /// Anyone can finish clearing the members of a removed role, up to `limit` per call.
+    pub fn clear_role_members(&mut self, hash: String, limit: u64) {
+        let initial_storage_usage = env::storage_usage();
+        let role = get_removed_roles().get(&hash).expect("removed role not found");
+        if role.clear_members(limit) == 0 {
+            get_removed_roles().remove(&hash);
+        }
+        set_storage_usage(initial_storage_usage, None);
+    }
+
+    /// Replaces the permissions denied to members of the role, denies only apply within the member's top override level.
+    pub fn set_role_deny(&mut self, hash: String, permissions: Vec<Permission>) {
+        let initial_storage_usage = env::storage_usage();
+        assert!(self.role_management.roles.contains_key(&hash), "role not exist");
+        assert!(self.can_execute_action(None, None, Permission::SetRole(Some(hash.clone()))), "not allowed");
+        let mut denies = get_role_denies();
+        if permissions.is_empty() {
+            denies.remove(&hash);
+        } else {
+            denies.insert(&hash, &permissions.into_iter().collect());
+        }
         set_storage_usage(initial_storage_usage, None);
     }
 
@@ -234,11 +488,43 @@ impl Community {
         let sender_id = get_predecessor_id();
         assert!(self.can_execute_action(None, None, Permission::AddMember(Some(hash.clone()))), "not allowed");
         let role = self.role_management.roles.get(&hash).expect(format!("{} not found", hash.as_str()).as_str());
-        let mut role_members: LookupMap<AccountId, HashMap<String, String>> = LookupMap::new(role.members.clone());
         let mod_level = self.get_user_mod_level(&sender_id);
         for (account_id, options) in members {
             assert!(mod_level > self.get_user_mod_level(&account_id), "not allowed");
-            role_members.insert(&account_id, &options.unwrap_or(HashMap::new()));
+            let options = options.unwrap_or(HashMap::new());
+            if let Some(until) = options.get(MEMBER_UNTIL) {
+                assert!(until.parse::<u64>().expect("invalid until") > env::block_timestamp(), "already expired");
+            }
+            role.add_member(&account_id, &options);
+        }
+        set_storage_usage(initial_storage_usage, None);
+    }
+
+    /// Anyone can drop memberships whose `until` has passed and get the storage back.
+    pub fn remove_expired_members(&mut self, hash: String, members: Vec<AccountId>) {
+        let initial_storage_usage = env::storage_usage();
+        let role = self.role_management.roles.get(&hash).expect(format!("{} not found", hash.as_str()).as_str());
+        let role_members = role.get_members();
+        for account_id in members {
+            if let Some(options) = role_members.get(&account_id) {
+                if is_expired_member(&options) {
+                    role.remove_member(&account_id);
+                }
+            }
+        }
+        set_storage_usage(initial_storage_usage, None);
+    }
+
+    /// Indexes members that were added before roles kept a member index.
+    pub fn sync_role_members(&mut self, hash: String, members: Vec<AccountId>) {
+        let initial_storage_usage = env::storage_usage();
+        let role = self.role_management.roles.get(&hash).expect(format!("{} not found", hash.as_str()).as_str());
+        let role_members = role.get_members();
+        let mut index = role.get_member_index();
+        for account_id in members {
+            if role_members.contains_key(&account_id) {
+                index.insert(&account_id);
+            }
         }
         set_storage_usage(initial_storage_usage, None);
     }
@@ -248,11 +534,10 @@ impl Community {
         let sender_id = get_predecessor_id();
         assert!(self.can_execute_action(None, None, Permission::RemoveMember(Some(hash.clone()))), "not allowed");
         let role = self.role_management.roles.get(&hash).expect(format!("{} not found", hash.as_str()).as_str());
-        let mut role_members: LookupMap<AccountId, HashMap<String, String>> = LookupMap::new(role.members.clone());
         let mod_level = self.get_user_mod_level(&sender_id);
         for account_id in members {
             assert!(mod_level > self.get_user_mod_level(&account_id), "not allowed");
-            role_members.remove(&account_id);
+            role.remove_member(&account_id);
         }
         set_storage_usage(initial_storage_usage, None);
     }
@@ -267,44 +552,51 @@ impl Community {
                 Some(role) => role,
                 None => continue,
             };
-            let mut role_members: LookupMap<AccountId, HashMap<String, String>> = LookupMap::new(role.members.clone());
             for account_id in members {
                 assert!(mod_level > self.get_user_mod_level(&account_id), "not allowed");
-                role_members.insert(&account_id, &HashMap::new());
+                role.add_member(&account_id, &HashMap::new());
             }
         }
 
         for (hash, members) in remove.iter() {
             assert!(self.can_execute_action(None, None, Permission::RemoveMember(Some(hash.clone()))), "not allowed");
             let role = match self.role_management.roles.get(hash) {
-                Some(role) => role,
+                Some(role) => role.clone(),
                 None => continue,
             };
-            let mut role_members: LookupMap<AccountId, HashMap<String, String>> = LookupMap::new(role.members.clone());
             for account_id in members {
                 assert!(self.can_execute_action(None, None, Permission::RemoveMember(Some(hash.clone()))), "not allowed");
-                role_members.remove(&account_id);
+                role.remove_member(&account_id);
             }
         }
         set_storage_usage(initial_storage_usage, None);
     }
 
+    pub fn get_user_override_level(&self, account_id: &AccountId) -> u32 {
+        if *account_id == self.owner_id || *account_id == env::current_account_id() {
+            return u32::MAX
+        }
+        self.role_management.roles.values()
+            .filter(|role| role.is_member(account_id))
+            .map(|role| role.override_level)
+            .max()
+            .unwrap_or(0)
+    }
+
     pub fn get_user_mod_level(&self, account_id: &AccountId) -> u32 {
         if *account_id == self.owner_id || *account_id == env::current_account_id() {
             return u32::MAX
         }
         let mut max_override_level = 0;
         for (hash, role) in self.role_management.roles.iter() {
-            let role_members: LookupMap<AccountId, HashMap<String, String>> = LookupMap::new(role.members.clone());
-            if role.override_level > max_override_level && role_members.contains_key(&account_id) {
+            if role.override_level > max_override_level && role.is_member(&account_id) {
                 max_override_level = role.override_level
             }
 
         }
         let mut max_mod_level = 0;
         for (hash, role) in self.role_management.roles.iter() {
-            let role_members: LookupMap<AccountId, HashMap<String, String>> = LookupMap::new(role.members.clone());
-            if role.override_level >= max_override_level && role_members.contains_key(&account_id) {
+            if role.override_level >= max_override_level && role.is_member(&account_id) {
                 if role.mod_level > max_mod_level {
                     max_mod_level = role.mod_level;
                 }
@@ -318,15 +610,13 @@ impl Community {
         let mut roles = HashMap::default();
         let mut max_override_level = 0;
         for (hash, role) in self.role_management.roles.iter() {
-            let role_members: LookupMap<AccountId, HashMap<String, String>> = LookupMap::new(role.members.clone());
-            if role.override_level > max_override_level && role_members.contains_key(&account_id) {
+            if role.override_level > max_override_level && role.is_member(&account_id) {
                 max_override_level = role.override_level
             }
 
         }
         for (hash, role) in self.role_management.roles.iter() {
-            let role_members: LookupMap<AccountId, HashMap<String, String>> = LookupMap::new(role.members.clone());
-            if role.override_level >= max_override_level && role_members.contains_key(&account_id) {
+            if role.override_level >= max_override_level && role.is_member(&account_id) {
                 roles.insert(hash.clone(), role.clone());
             }
         }
@@ -367,6 +657,9 @@ impl Community {
             let mut allowed = false;
             let mut max_override_level = 0;
             let roles = self.get_user_roles(&account_id);
+            if roles.iter().any(|(hash, role)| check_denied(&permission, &get_role_deny(hash), &role.permissions)) {
+                return false
+            }
             for (_, role) in roles.into_iter() {
                 max_override_level = role.override_level;
                 if self.check_allowed(&permission, &role.permissions, &account_id) {
@@ -460,7 +753,10 @@ impl Community {
                             None => return Some(false)
                         }
                     },
-                    _ => return Some(false)
+                    _ => match get_content_fallback(permission).and_then(|fallback| permissions.get(&fallback)) {
+                        Some(val) => val,
+                        None => return Some(false)
+                    }
                 }
             }
         };
@@ -527,7 +823,7 @@ impl Community {
                 }
             },
             Permission::Other(_) => permissions.contains(&permission) || permissions.contains(&Permission::Other(None)),
-            _ => permissions.contains(&permission)
+            _ => permissions.contains(&permission) || get_content_fallback(permission).map_or(false, |fallback| permissions.contains(&fallback))
         }
     }
 
@@ -537,13 +833,34 @@ impl Community {
 
 #[cfg(test)]
 mod tests {
-    use std::{convert::TryInto, collections::{HashMap, HashSet}};
+    use std::{convert::TryInto, collections::{HashMap, HashSet}, str::FromStr};
 
-    use near_sdk::AccountId;
+    use near_sdk::{AccountId, env, collections::LookupMap};
 
     use crate::account::{Relationship, Access};
 
-    use super::{RoleManagement, Permission};
+    use crate::test_utils::{account, setup_community};
+    use std::convert::TryFrom;
+
+    use near_sdk::json_types::Base58CryptoHash;
+
+    use crate::Community;
+    use crate::test_utils::set_context;
+    use super::{RoleManagement, Permission, Role, RoleUpdate, MEMBER_UNTIL, check_denied, get_removed_roles};
+
+    /// A "target" role and an "editor" role allowed to edit it, with dave.testnet as editor.
+    fn setup_role_editor() -> (Community, String) {
+        let mut community = setup_community(HashMap::new());
+        let target = community.add_role("target".to_string(), vec![Permission::Like], 1, 0);
+        let editor = community.add_role("editor".to_string(), vec![Permission::SetRole(None), Permission::Like, Permission::Unlike, Permission::Share], 5, 1);
+        community.role_management.roles.get(&editor).unwrap().add_member(&account("dave.testnet"), &HashMap::new());
+        set_context(&account("dave.testnet"));
+        (community, target)
+    }
+
+    fn get_permissions(community: &Community, hash: &str) -> HashSet<Permission> {
+        community.role_management.roles.get(hash).unwrap().permissions.clone()
+    }
 
     fn check_global_allowed(permission: &Permission, permissions: HashMap<Permission, (Relationship, Option<Access>)>) -> Option<bool> {
         let relationship = match permission {
@@ -635,6 +952,139 @@ mod tests {
         }
     }
 
+    #[test]
+    pub fn test_member_until() {
+        let role = Role {
+            alias: "trial".to_string(),
+            members: "trial_member".to_string().into_bytes(),
+            permissions: HashSet::new(),
+            mod_level: 1,
+            override_level: 0
+        };
+        let mut role_members: LookupMap<AccountId, HashMap<String, String>> = LookupMap::new(role.members.clone());
+        let expired = AccountId::from_str("expired.testnet").unwrap();
+        let active = AccountId::from_str("active.testnet").unwrap();
+        let permanent = AccountId::from_str("permanent.testnet").unwrap();
+        let mut options = HashMap::new();
+        options.insert(MEMBER_UNTIL.to_string(), env::block_timestamp().to_string());
+        role_members.insert(&expired, &options);
+        options.insert(MEMBER_UNTIL.to_string(), (env::block_timestamp() + 1).to_string());
+        role_members.insert(&active, &options);
+        role_members.insert(&permanent, &HashMap::new());
+        assert!(!role.is_member(&expired));
+        assert!(role.is_member(&active));
+        assert!(role.is_member(&permanent));
+    }
+
+    #[test]
+    pub fn test_check_denied() {
+        let mut denied = HashSet::new();
+        denied.insert(Permission::Like);
+        denied.insert(Permission::AddContent(2));
+        denied.insert(Permission::SetRole(None));
+        let allowed = HashSet::new();
+        assert!(check_denied(&Permission::Like, &denied, &allowed));
+        assert!(check_denied(&Permission::AddContent(2), &denied, &allowed));
+        assert!(!check_denied(&Permission::AddContent(1), &denied, &allowed));
+        assert!(!check_denied(&Permission::Unlike, &denied, &allowed));
+        assert!(check_denied(&Permission::SetRole(Some("role".to_string())), &denied, &allowed));
+        assert!(!check_denied(&Permission::DelRole(Some("role".to_string())), &denied, &allowed));
+    }
+
+    #[test]
+    pub fn test_check_denied_fallback() {
+        let mut denied = HashSet::new();
+        denied.insert(Permission::AddContent(2));
+        let mut allowed = HashSet::new();
+        assert!(check_denied(&Permission::AddContent(5), &denied, &allowed));
+        assert!(!check_denied(&Permission::AddEncryptContent(5), &denied, &allowed));
+        allowed.insert(Permission::AddContent(5));
+        assert!(!check_denied(&Permission::AddContent(5), &denied, &allowed));
+        assert!(check_denied(&Permission::AddContent(6), &denied, &allowed));
+        denied.insert(Permission::AddContent(5));
+        assert!(check_denied(&Permission::AddContent(5), &denied, &allowed));
+    }
+
+    #[test]
+    pub fn test_member_index() {
+        let role = Role {
+            alias: "indexed".to_string(),
+            members: "indexed_member".to_string().into_bytes(),
+            permissions: HashSet::new(),
+            mod_level: 1,
+            override_level: 0
+        };
+        let alice = AccountId::from_str("alice.testnet").unwrap();
+        let bob = AccountId::from_str("bob.testnet").unwrap();
+        let carol = AccountId::from_str("carol.testnet").unwrap();
+        role.add_member(&alice, &HashMap::new());
+        role.add_member(&bob, &HashMap::new());
+        role.add_member(&carol, &HashMap::new());
+        role.add_member(&bob, &HashMap::new());
+        assert!(role.get_member_index().len() == 3);
+        role.remove_member(&alice);
+        assert!(!role.is_member(&alice));
+        assert!(role.get_member_index().get(0, 10) == vec![carol.clone(), bob.clone()]);
+        assert!(role.get_member_index().get(1, 1) == vec![bob]);
+        assert!(role.clear_members(1) == 1);
+        assert!(role.get_member_index().get(0, 10) == vec![carol.clone()]);
+        assert!(role.clear_members(10) == 0);
+        assert!(role.get_member_index().len() == 0);
+        assert!(!role.is_member(&carol));
+    }
+
+    #[test]
+    pub fn test_set_role() {
+        let (mut community, target) = setup_role_editor();
+        let hash = Base58CryptoHash::try_from(target.clone()).unwrap();
+        community.set_role(hash, RoleUpdate {
+            replace_permissions: Some(vec![Permission::Unlike, Permission::Share]),
+            ..Default::default()
+        });
+        assert_eq!(get_permissions(&community, &target), vec![Permission::Unlike, Permission::Share].into_iter().collect());
+
+        community.set_role(hash, RoleUpdate {
+            permissions: Some(vec![Permission::Like]),
+            remove_permissions: Some(vec![Permission::Unlike]),
+            ..Default::default()
+        });
+        assert_eq!(get_permissions(&community, &target), vec![Permission::Like, Permission::Share].into_iter().collect());
+
+        // dave's own override level is 1, so the role can't be lifted to it or past it
+        community.set_role(hash, RoleUpdate { override_level: Some(5), mod_level: Some(5), ..Default::default() });
+        let role = community.role_management.roles.get(&target).unwrap();
+        assert_eq!((role.override_level, role.mod_level), (0, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "not allowed")]
+    pub fn test_set_role_escalation() {
+        let (mut community, target) = setup_role_editor();
+        community.set_role(Base58CryptoHash::try_from(target).unwrap(), RoleUpdate {
+            permissions: Some(vec![Permission::DelOthersContent]),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    pub fn test_clear_role_members() {
+        let mut community = setup_community(HashMap::new());
+        let hash = community.add_role("trial".to_string(), vec![], 1, 0);
+        let role = community.role_management.roles.get(&hash).unwrap().clone();
+        for member in ["alice.testnet", "bob.testnet", "carol.testnet"] {
+            role.add_member(&account(member), &HashMap::new());
+        }
+        community.remove_role(hash.clone());
+        assert!(!community.role_management.roles.contains_key(&hash));
+        assert!(role.is_member(&account("alice.testnet")));
+
+        community.clear_role_members(hash.clone(), 2);
+        assert!(role.get_member_index().get(0, 10) == vec![account("alice.testnet")]);
+        community.clear_role_members(hash.clone(), 2);
+        assert!(!role.is_member(&account("alice.testnet")));
+        assert!(!get_removed_roles().contains_key(&hash));
+    }
+
     #[test]
     pub fn test() {
         let mut permissions = HashMap::new();
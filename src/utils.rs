@@ -6,6 +6,7 @@ use ed25519_dalek::Verifier;
 use near_sdk::{Balance, StorageUsage, Promise, log};
 
 use crate::*;
+use crate::post::is_live;
 
 
 pub(crate) fn refund_extra_storage_deposit(storage_used: StorageUsage, used_balance: Balance) {
@@ -100,7 +101,17 @@ where T: BorshSerialize + BorshDeserialize
 }
 
 
+/// Hash of the content, `None` if any level is not stored or is outside its `publish_at`/`expire_at` window.
 pub(crate) fn get_content_hash(hierarchies: Vec<Hierarchy>, extra: Option<String>, only_hash: bool) -> Option<String> {
+    internal_get_content_hash(hierarchies, extra, only_hash, true)
+}
+
+/// Like `get_content_hash` but ignores publish windows, for authors and moderators managing stored content.
+pub(crate) fn get_stored_content_hash(hierarchies: Vec<Hierarchy>) -> Option<String> {
+    internal_get_content_hash(hierarchies, None, false, false)
+}
+
+fn internal_get_content_hash(hierarchies: Vec<Hierarchy>, extra: Option<String>, only_hash: bool, check_live: bool) -> Option<String> {
     let mut hash_prefix = "".to_string();
     for (_, hierarchy) in hierarchies.iter().enumerate() {
         let mut hierarchy_str = hash_prefix + &hierarchy.account_id.to_string() + &String::from(&hierarchy.target_hash);
@@ -114,30 +125,45 @@ pub(crate) fn get_content_hash(hierarchies: Vec<Hierarchy>, extra: Option<String
         if !only_hash && !check(&hierarchy_hash) {
             return None
         }
+        if !only_hash && check_live && !is_live(&hierarchy.options) {
+            return None
+        }
         let hierarchy_hash: [u8;32] = hierarchy_hash[..].try_into().unwrap();
         hash_prefix = String::from(&Base58CryptoHash::from(hierarchy_hash));
     }
     Some(hash_prefix)
 }
 
-pub(crate) fn set_content(args: String, account_id: AccountId, hash_prefix: String, options:Option<HashMap<String, String>>, extra: Option<String>) -> Base58CryptoHash {
-    let args = args.clone() + &env::block_timestamp().to_string();    //&bs58::encode(env::block_timestamp()).into_string();
-    let target_hash = env::sha256(&args.clone().into_bytes());
-    let target_hash: [u8;32] = target_hash[..].try_into().unwrap();
-
-    let mut hierarchy_str = hash_prefix + &account_id.to_string() + &String::from(&Base58CryptoHash::from(target_hash));
-    if let Some(options) = options {
-        hierarchy_str += &json!(options).to_string();
+/// `target_hash` of content is `sha256(args + timestamp)`, identical content landing in the same block under
+/// the same parent and author gets `sha256(args + timestamp + "#" + nonce)` with the smallest free nonce instead.
+pub(crate) fn get_target_hash(args: &str, timestamp: u64, nonce: u32) -> CryptoHash {
+    let mut args = args.to_string() + &timestamp.to_string();
+    if nonce > 0 {
+        args += &format!("#{}", nonce);
     }
-    if let Some(extra) = extra {
-        hierarchy_str += &extra;
-    }    
+    env::sha256(&args.into_bytes())[..].try_into().unwrap()
+}
 
-    let hash = env::sha256(&hierarchy_str.into_bytes());
-    //let hash: CryptoHash = hash[..].try_into().unwrap();
-    
-    set(&hash, 0);
-    Base58CryptoHash::from(target_hash)
+/// Returns the `target_hash`, the collision nonce and the content's leaf index in `mmr`.
+pub(crate) fn set_content(args: String, account_id: AccountId, hash_prefix: String, options:Option<HashMap<String, String>>, extra: Option<String>) -> (Base58CryptoHash, u32, u64) {
+    let mut nonce = 0;
+    loop {
+        let target_hash = get_target_hash(&args, env::block_timestamp(), nonce);
+        let mut hierarchy_str = hash_prefix.clone() + &account_id.to_string() + &String::from(&Base58CryptoHash::from(target_hash));
+        if let Some(options) = options.clone() {
+            hierarchy_str += &json!(options).to_string();
+        }
+        if let Some(extra) = extra.clone() {
+            hierarchy_str += &extra;
+        }
+
+        let hash = env::sha256(&hierarchy_str.into_bytes());
+        if !check(&hash) {
+            set(&hash, 0);
+            return (Base58CryptoHash::from(target_hash), nonce, mmr::append(&hash))
+        }
+        nonce += 1;
+    }
 }
 
 // pub(crate) fn is_registered(account_id: &AccountId) -> bool {
@@ -148,13 +174,21 @@ pub(crate) fn set_content(args: String, account_id: AccountId, hash_prefix: Stri
 //     }
 // }
 
+pub(crate) fn lookup_account(account_id: &AccountId) -> Option<Account> {
+    let accounts: LookupMap<AccountId, VAccount> = LookupMap::new(StorageKey::VAccount);
+    if let Some(v) = accounts.get(account_id) {
+        return Some(v.into())
+    }
+    // accounts written before the typed layout are migrated on first read and moved on next write
+    let old_accounts: LookupMap<AccountId, OldAccount> = LookupMap::new(StorageKey::Account);
+    old_accounts.get(account_id).map(|v| v.migrate(account_id))
+}
+
 pub(crate) fn get_account(account_id: &AccountId) -> Account {
-    let accounts: LookupMap<AccountId, Account> = LookupMap::new(StorageKey::Account);
-    match accounts.get(account_id) {
+    match lookup_account(account_id) {
         Some(mut v) => {
-            v.data.insert("account_id".to_string(), account_id.to_string());
             if let AccessLimit::Free = get_access_limit() {
-                v.data.insert("registered".to_string(), json!(true).to_string());
+                v.registered = true;
             }
             v
         },
@@ -163,8 +197,12 @@ pub(crate) fn get_account(account_id: &AccountId) -> Account {
 }
 
 pub(crate) fn set_account(account_id: &AccountId, account: &Account) {
-    let mut accounts: LookupMap<AccountId, Account> = LookupMap::new(StorageKey::Account);
-    accounts.insert(account_id, account);
+    let mut accounts: LookupMap<AccountId, VAccount> = LookupMap::new(StorageKey::VAccount);
+    accounts.insert(account_id, &account.clone().into());
+    let mut old_accounts: LookupMap<AccountId, OldAccount> = LookupMap::new(StorageKey::Account);
+    if old_accounts.contains_key(account_id) {
+        old_accounts.remove(account_id);
+    }
 }
 
 pub(crate) fn get_arg<T>(key: &str) -> Option<T> 
@@ -191,7 +229,6 @@ pub(crate) fn set_storage_usage(initial_storage_usage: u64, account_id: Option<A
     if let AccessLimit::Free = get_access_limit() {
         return
     }
-    let mut accounts: LookupMap<AccountId, Account> = LookupMap::new(StorageKey::Account);
     let account_id = match account_id {
         Some(account_id) => account_id,
         None => env::signer_account_id()
@@ -209,7 +246,7 @@ pub(crate) fn set_storage_usage(initial_storage_usage: u64, account_id: Option<A
         let storage_usage = initial_storage_usage - current_storage_usage;
         account.increase_balance(balance, storage_usage as u128 * env::storage_byte_cost())
     }
-    accounts.insert(&account_id, &account);
+    set_account(&account_id, &account);
 }
 
 pub(crate) fn from_rpc_sig(buf: &[u8]) -> (Vec<u8>, u8) {
@@ -282,7 +319,13 @@ pub(crate) fn init_callback() {
 
 #[cfg(test)]
 mod tests {
+    use std::{convert::TryFrom, str::FromStr};
+
     use near_sdk::borsh::{BorshSerialize, BorshDeserialize};
+    use near_sdk::{AccountId, CryptoHash, json_types::Base58CryptoHash};
+
+    use super::{set_content, check, get_content_hash, get_target_hash};
+    use crate::post::Hierarchy;
 
     
 
@@ -292,4 +335,25 @@ mod tests {
         let a2: u8 = BorshDeserialize::deserialize(&mut a1.as_slice()).unwrap();
         print!("{:?}, {:?}", a1, a2);
     }
+
+    #[test]
+    pub fn test_same_block_duplicates() {
+        let alice = AccountId::from_str("alice.testnet").unwrap();
+        let bob = AccountId::from_str("bob.testnet").unwrap();
+        let args = "gm".to_string();
+        let (first, first_nonce, _) = set_content(args.clone(), alice.clone(), "".to_string(), None, None);
+        let (second, second_nonce, _) = set_content(args.clone(), alice.clone(), "".to_string(), None, None);
+        let (third, third_nonce, _) = set_content(args.clone(), bob.clone(), "".to_string(), None, None);
+        assert_eq!((first_nonce, second_nonce, third_nonce), (0, 1, 0));
+        assert!(first != second);
+        assert!(first == third);
+        assert!(CryptoHash::from(first) == get_target_hash(&args, 0, 0));
+        assert!(CryptoHash::from(second) == get_target_hash(&args, 0, 1));
+
+        for (target_hash, account_id) in [(first, alice.clone()), (second, alice), (third, bob)] {
+            let hierarchies = vec![Hierarchy { target_hash, account_id, options: None }];
+            let hierarchy_hash = get_content_hash(hierarchies, None, false).expect("content not found");
+            assert!(check(&CryptoHash::from(Base58CryptoHash::try_from(hierarchy_hash).unwrap())));
+        }
+    }
 }
\ No newline at end of file
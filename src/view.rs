@@ -3,8 +3,12 @@ use std::collections::HashMap;
 use crate::{*, utils::get, proposal::{ProposalStatus, Proposal, Opt, ExecutionStatus}};
 use near_sdk::Balance;
 use utils::{get_content_hash, get_account as get_account_safe};
-use post::Hierarchy;
+use post::{Hierarchy, ContentRevision};
 use account::AssetKey;
+use drip::DecayConfig;
+use mmr::{ContentRoot, ContentProof};
+use pin::PinnedContent;
+use report::ReportOutput;
 
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
@@ -13,7 +17,47 @@ pub struct RoleOutput {
     pub alias: String,
     pub permissions: HashSet<Permission>,
     pub mod_level: u32,
-    pub override_level: u32  
+    pub override_level: u32,
+    pub member_count: U64,
+    pub deny_permissions: HashSet<Permission>
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[derive(Debug)]
+pub struct FollowCountOutput {
+    pub followers: U64,
+    pub following: U64
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[derive(Debug)]
+pub struct AccountOutput {
+    pub account_id: AccountId,
+    pub registered: bool,
+    pub permanent: bool,
+    pub drip: U128,
+    pub content_count: U64,
+    pub total_content_count: U64,
+    pub balances: Vec<(AssetKey, U128)>,
+    pub app_data: HashMap<AccountId, HashMap<String, String>>
+}
+
+impl From<Account> for AccountOutput {
+    fn from(account: Account) -> Self {
+        let content_count = account.get_action_count(drip::CONTENT_ACTION);
+        Self {
+            account_id: account.account_id,
+            registered: account.registered,
+            permanent: account.permanent,
+            drip: account.drip.into(),
+            content_count: content_count.into(),
+            total_content_count: account.activity.total_content_count.into(),
+            balances: account.balances.into_iter().map(|(asset, balance)| (asset, balance.into())).collect(),
+            app_data: account.app_data
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -43,12 +87,42 @@ impl Community {
         self.drip.get_drip(account_id)
     }
 
-    pub fn get_account_decay(&self, account_id: AccountId) -> u32 {
-        self.drip.get_account_decay(account_id)
+    pub fn get_drip_table(&self) -> HashMap<String, U128> {
+        drip::get_drip_table()
+    }
+
+    pub fn get_account_decay(&self, account_id: AccountId, action: Option<String>) -> u32 {
+        self.drip.get_account_decay(account_id, &action.unwrap_or(drip::CONTENT_ACTION.to_string()))
+    }
+
+    pub fn get_decay_config(&self) -> DecayConfig {
+        drip::get_decay_config()
     }
 
-    pub fn get_account(&self, account_id: AccountId) -> HashMap<String, String> {
-        get_account_safe(&account_id).data
+    pub fn get_removal(&self, hierarchies: Vec<Hierarchy>) -> Option<appeal::Removal> {
+        let hierarchy_hash = get_content_hash(hierarchies, None, true).unwrap();
+        appeal::get_removal(&hierarchy_hash)
+    }
+
+    pub fn get_report_config(&self) -> report::ReportConfig {
+        report::get_report_config()
+    }
+
+    pub fn is_hidden(&self, hierarchies: Vec<Hierarchy>) -> bool {
+        let hierarchy_hash = utils::get_stored_content_hash(hierarchies).expect("content not found");
+        report::is_hidden(&hierarchy_hash)
+    }
+
+    pub fn get_max_depth(&self) -> u8 {
+        post::get_max_depth() as u8
+    }
+
+    pub fn get_account(&self, account_id: AccountId) -> AccountOutput {
+        get_account_safe(&account_id).into()
+    }
+
+    pub fn get_app_data(&self, account_id: AccountId, app_id: AccountId) -> Option<HashMap<String, String>> {
+        get_account_safe(&account_id).get_app_data(&app_id)
     }
 
     pub fn get_content_decay(&self, hierarchies: Vec<Hierarchy>) -> u32 {
@@ -58,7 +132,81 @@ impl Community {
             let prev_hash = CryptoHash::from(Base58CryptoHash::try_from(hierarchy_hash).unwrap()).to_vec();
             content_count = get::<u8>(&prev_hash).unwrap();
         }
-        self.drip.get_content_decay(content_count as u32)
+        self.drip.get_content_decay(hierarchies.len() as u8, content_count as u32)
+    }
+
+    /// Whether a key-release service should hand the content key to the account:
+    /// the author, a valid decryption receipt, or meeting a non-payment access condition.
+    pub fn can_decrypt(&self, account_id: AccountId, hierarchies: Vec<Hierarchy>) -> bool {
+        let hierarchy_hash = get_content_hash(hierarchies.clone(), None, false).expect("content not found");
+        let hierarchy = hierarchies.get(hierarchies.len() - 1).unwrap();
+        let access = post::get_encrypt_access(&hierarchy.options).expect("not encrypted");
+        if hierarchy.account_id == account_id || post::get_decrypt_receipt(&account_id, &hierarchy_hash).is_some() {
+            return true
+        }
+        !access.is_payment && get_account_safe(&account_id).check_condition(&access)
+    }
+
+    pub fn get_decrypt_receipt(&self, account_id: AccountId, hierarchies: Vec<Hierarchy>) -> Option<U64> {
+        let hierarchy_hash = get_content_hash(hierarchies, None, false).expect("content not found");
+        post::get_decrypt_receipt(&account_id, &hierarchy_hash).map(|v| v.into())
+    }
+
+    pub fn get_pinned_content(&self, from: u32, limit: u32) -> Vec<PinnedContent> {
+        pin::get_pinned().into_iter().skip(from as usize).take(limit as usize).collect()
+    }
+
+    pub fn get_reaction_kinds(&self) -> HashSet<String> {
+        reaction::get_reaction_kinds()
+    }
+
+    pub fn get_reactions(&self, hierarchies: Vec<Hierarchy>) -> HashMap<String, U64> {
+        let hierarchy_hash = get_content_hash(hierarchies, None, false).expect("content not found");
+        reaction::get_reaction_counts(&hierarchy_hash).into_iter().map(|(k, v)| (k, v.into())).collect()
+    }
+
+    pub fn get_reaction(&self, account_id: AccountId, hierarchies: Vec<Hierarchy>) -> Option<String> {
+        let hierarchy_hash = get_content_hash(hierarchies, None, false).expect("content not found");
+        reaction::get_reaction(&account_id, &hierarchy_hash)
+    }
+
+    pub fn get_like_count(&self, hierarchies: Vec<Hierarchy>) -> U64 {
+        let hierarchy_hash = get_content_hash(hierarchies, None, false).expect("content not found");
+        post::get_like_count(&hierarchy_hash).into()
+    }
+
+    pub fn is_liked(&self, account_id: AccountId, hierarchies: Vec<Hierarchy>) -> bool {
+        let hierarchy_hash = get_content_hash(hierarchies, None, false).expect("content not found");
+        utils::check(&env::sha256(&(account_id.to_string() + "like" + &hierarchy_hash).into_bytes()))
+    }
+
+    pub fn get_follow_count(&self, account_id: AccountId) -> FollowCountOutput {
+        let count = follow::get_follow_count(&account_id);
+        FollowCountOutput {
+            followers: count.followers.into(),
+            following: count.following.into()
+        }
+    }
+
+    pub fn is_private(&self, account_id: AccountId) -> bool {
+        follow::is_private(&account_id)
+    }
+
+    pub fn get_follow_requests(&self, account_id: AccountId, from: u64, limit: u64) -> follow::FollowRequestPage {
+        follow::get_follow_requests(&account_id, from, limit)
+    }
+
+    pub fn get_account_relation(&self, account_id: AccountId, target_id: AccountId) -> follow::AccountRelation {
+        follow::get_relation(&account_id, &target_id)
+    }
+
+    pub fn check_shared(&self, account_id: AccountId, hierarchies: Vec<Hierarchy>, through: Option<AccountId>) -> bool {
+        let hierarchy_hash = get_content_hash(hierarchies, None, false).expect("content not found");
+        let view_hash = match through {
+            Some(through) => env::sha256(&(account_id.to_string() + "viewed" + &hierarchy_hash + "through" + through.as_str()).into_bytes()),
+            None => env::sha256(&(account_id.to_string() + "share" + &hierarchy_hash).into_bytes())
+        };
+        utils::check(&view_hash)
     }
 
     // pub fn check_invited(&self, inviter_id: AccountId, invitee_id: AccountId) -> bool {
@@ -83,23 +231,58 @@ impl Community {
                 alias: role.alias.clone(), 
                 permissions: role.permissions.clone(), 
                 mod_level: role.mod_level, 
-                override_level: role.override_level 
+                override_level: role.override_level,
+                member_count: role.get_member_index().len().into(),
+                deny_permissions: role::get_role_deny(hash)
             });
         }
         roles
     }
 
+    pub fn get_role_members(&self, hash: String, from: u64, limit: u64) -> Vec<(AccountId, HashMap<String, String>)> {
+        let role = self.role_management.roles.get(&hash).expect(format!("{} not found", hash.as_str()).as_str());
+        role.get_member_index().get(from, limit).into_iter().filter_map(|account_id| {
+            role.get_member(&account_id).map(|options| (account_id, options))
+        }).collect()
+    }
+
+    pub fn get_member_roles(&self, account_id: AccountId) -> Vec<String> {
+        self.role_management.roles.iter().filter(|(_, role)| role.is_member(&account_id)).map(|(hash, _)| hash.clone()).collect()
+    }
+
+    /// Edit chain head of the content, `None` while it has never been edited.
+    pub fn get_content_revision(&self, hierarchies: Vec<Hierarchy>) -> Option<ContentRevision> {
+        let hierarchy_hash = get_content_hash(hierarchies, None, false).expect("content not found");
+        post::get_content_revision(&hierarchy_hash)
+    }
+
+    /// Root of the content accumulator, at `leaf_count` leaves or the current size.
+    pub fn get_content_root(&self, leaf_count: Option<U64>) -> ContentRoot {
+        mmr::get_root(leaf_count.map(|v| v.0))
+    }
+
+    /// Inclusion proof of a content leaf, verifiable off-chain with `community-verifier`.
+    pub fn get_content_proof(&self, leaf_index: U64, leaf_count: Option<U64>) -> ContentProof {
+        mmr::get_proof(leaf_index.0, leaf_count.map(|v| v.0))
+    }
+
     pub fn get_balance(&self, account_id: AccountId, balance: AssetKey) -> U128{
         get_account_safe(&account_id).get_balance(&balance).into()
     }
 
-    // pub fn get_reports(&self, account_id: AccountId) -> Vec<Report> {
-    //     let account = match self.reports.get(&account_id) {
-    //         Some(v) => v,
-    //         None => return Vec::new()
-    //     };
-    //     account.values().collect()
-    // }
+    /// Report queue for moderators, most reported content first.
+    /// Reports made before the queue was kept show up once `sync_report_queue` has run over them.
+    pub fn get_reports(&self, from: u64, limit: u64) -> Vec<ReportOutput> {
+        report::get_queued_reports(from, limit).into_iter().filter_map(|hierarchy_hash| {
+            self.reports.get(&hierarchy_hash).map(|accounts| report::get_report_output(hierarchy_hash, accounts))
+        }).collect()
+    }
+
+    pub fn get_report(&self, hierarchies: Vec<Hierarchy>) -> Option<ReportOutput> {
+        let hierarchy_hash = utils::get_stored_content_hash(hierarchies).expect("content not found");
+        let hierarchy_hash = Base58CryptoHash::try_from(hierarchy_hash).unwrap();
+        self.reports.get(&hierarchy_hash).map(|accounts| report::get_report_output(hierarchy_hash, accounts))
+    }
 
     pub fn get_proposal(&self, id: String) -> ProposalOutput {
         let proposal: Proposal = self.proposals.get(&id).unwrap().into();
@@ -0,0 +1,186 @@
+//! Off-chain verification of community content.
+//!
+//! Every content added to a community is appended to a Merkle Mountain Range over its hierarchy hash.
+//! With the raw `Hierarchy` chain of a content, a proof from `get_content_proof` and a root obtained
+//! from a trusted source (e.g. the `mmr_root` logged by `add_content`), anyone can check the content
+//! was stored without trusting the node serving the proof.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+pub type Hash = [u8; 32];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hierarchy {
+    /// base58 encoded `target_hash`
+    pub target_hash: String,
+    pub account_id: String,
+    pub options: Option<BTreeMap<String, String>>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentProof {
+    pub leaf_index: u64,
+    pub leaf_count: u64,
+    pub siblings: Vec<Hash>,
+    pub peaks: Vec<Hash>
+}
+
+fn sha256(data: &[u8]) -> Hash {
+    Sha256::digest(data).into()
+}
+
+pub fn decode_hash(hash: &str) -> Result<Hash, String> {
+    let bytes = bs58::decode(hash).into_vec().map_err(|e| e.to_string())?;
+    if bytes.len() != 32 {
+        return Err("invalid hash length".to_string())
+    }
+    let mut res = [0u8; 32];
+    res.copy_from_slice(&bytes);
+    Ok(res)
+}
+
+pub fn encode_hash(hash: &Hash) -> String {
+    bs58::encode(hash).into_string()
+}
+
+/// Same chaining as the contract's `get_content_hash`, returns the hash of the last hierarchy.
+pub fn get_hierarchy_hash(hierarchies: &[Hierarchy]) -> Hash {
+    let mut hash = [0u8; 32];
+    let mut hash_prefix = "".to_string();
+    for hierarchy in hierarchies {
+        let mut hierarchy_str = hash_prefix + &hierarchy.account_id + &hierarchy.target_hash;
+        if let Some(options) = &hierarchy.options {
+            hierarchy_str += &serde_json::to_string(options).unwrap();
+        }
+        hash = sha256(hierarchy_str.as_bytes());
+        hash_prefix = encode_hash(&hash);
+    }
+    hash
+}
+
+pub fn hash_leaf(hash: &Hash) -> Hash {
+    sha256(&[&[0u8][..], &hash[..]].concat())
+}
+
+pub fn hash_node(left: &Hash, right: &Hash) -> Hash {
+    sha256(&[&[1u8][..], &left[..], &right[..]].concat())
+}
+
+/// (height, first leaf) of every mountain for `leaf_count` leaves, highest first.
+pub fn get_mountains(leaf_count: u64) -> Vec<(u32, u64)> {
+    let mut mountains = Vec::new();
+    let mut start = 0;
+    for height in (0..64).rev() {
+        if leaf_count >> height & 1 == 1 {
+            mountains.push((height, start));
+            start += 1 << height;
+        }
+    }
+    mountains
+}
+
+pub fn bag_peaks(peaks: &[Hash]) -> Hash {
+    let mut iter = peaks.iter().rev();
+    let mut root = match iter.next() {
+        Some(v) => *v,
+        None => return [0u8; 32]
+    };
+    for peak in iter {
+        root = hash_node(peak, &root);
+    }
+    root
+}
+
+/// Checks the content with this `Hierarchy` chain is leaf `proof.leaf_index` under `root`.
+pub fn verify(hierarchies: &[Hierarchy], proof: &ContentProof, root: &Hash) -> bool {
+    if proof.leaf_index >= proof.leaf_count {
+        return false
+    }
+    let mountains = get_mountains(proof.leaf_count);
+    if mountains.len() != proof.peaks.len() || bag_peaks(&proof.peaks) != *root {
+        return false
+    }
+    let (k, (height, start)) = match mountains.iter().enumerate().find(|(_, (height, start))| proof.leaf_index < start + (1 << height)) {
+        Some((k, mountain)) => (k, *mountain),
+        None => return false
+    };
+    if proof.siblings.len() != height as usize {
+        return false
+    }
+    let mut node = hash_leaf(&get_hierarchy_hash(hierarchies));
+    let offset = proof.leaf_index - start;
+    for (level, sibling) in proof.siblings.iter().enumerate() {
+        node = if offset >> level & 1 == 0 { hash_node(&node, sibling) } else { hash_node(sibling, &node) };
+    }
+    node == proof.peaks[k]
+}
+
+fn parse_u64(value: &Value) -> Result<u64, String> {
+    match value {
+        Value::String(v) => v.parse().map_err(|_| "invalid number".to_string()),
+        Value::Number(v) => v.as_u64().ok_or_else(|| "invalid number".to_string()),
+        _ => Err("invalid number".to_string())
+    }
+}
+
+fn parse_hashes(value: &Value) -> Result<Vec<Hash>, String> {
+    value.as_array().ok_or_else(|| "invalid hashes".to_string())?
+        .iter()
+        .map(|v| decode_hash(v.as_str().ok_or_else(|| "invalid hash".to_string())?))
+        .collect()
+}
+
+impl ContentProof {
+    /// Parses the JSON returned by the contract's `get_content_proof` view.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let value: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        Ok(Self {
+            leaf_index: parse_u64(&value["leaf_index"])?,
+            leaf_count: parse_u64(&value["leaf_count"])?,
+            siblings: parse_hashes(&value["siblings"])?,
+            peaks: parse_hashes(&value["peaks"])?
+        })
+    }
+}
+
+impl Hierarchy {
+    /// Parses hierarchies as they appear in contract calls and `ContentAdd` events.
+    pub fn from_json(json: &str) -> Result<Vec<Self>, String> {
+        let value: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        value.as_array().ok_or_else(|| "invalid hierarchies".to_string())?
+            .iter()
+            .map(|v| Ok(Self {
+                target_hash: v["target_hash"].as_str().ok_or_else(|| "invalid target_hash".to_string())?.to_string(),
+                account_id: v["account_id"].as_str().ok_or_else(|| "invalid account_id".to_string())?.to_string(),
+                options: match &v["options"] {
+                    Value::Null => None,
+                    options => Some(serde_json::from_value(options.clone()).map_err(|e| e.to_string())?)
+                }
+            }))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_mountains() {
+        assert_eq!(get_mountains(0), vec![]);
+        assert_eq!(get_mountains(1), vec![(0, 0)]);
+        assert_eq!(get_mountains(7), vec![(2, 0), (1, 4), (0, 6)]);
+    }
+
+    #[test]
+    pub fn test_single_leaf() {
+        let hierarchies = Hierarchy::from_json(r#"[{"target_hash":"5EVZZTdCcMQ6Di5fq2Zw1HuFd4chQ9KK4DG3byVwiSyp","account_id":"tokenq.testnet","options":null}]"#).unwrap();
+        let leaf = hash_leaf(&get_hierarchy_hash(&hierarchies));
+        let proof = ContentProof { leaf_index: 0, leaf_count: 1, siblings: vec![], peaks: vec![leaf] };
+        assert!(verify(&hierarchies, &proof, &leaf));
+        assert!(!verify(&hierarchies, &proof, &[0u8; 32]));
+    }
+}